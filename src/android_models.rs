@@ -0,0 +1,188 @@
+//! Android model codes mapped to brands and marketing names.
+//!
+//! Entries are matched by prefix, so `SM-A536` covers the regional variants
+//! `SM-A536B`, `SM-A536U`, `SM-A536E` and so on. When several prefixes match, the
+//! longest one wins.
+
+/// `(model code prefix, brand, marketing name)`
+const ANDROID_MODELS: &[(&str, &str, &str)] = &[
+    // Samsung Galaxy S
+    ("SM-S921", "Samsung", "Galaxy S24"),
+    ("SM-S926", "Samsung", "Galaxy S24+"),
+    ("SM-S928", "Samsung", "Galaxy S24 Ultra"),
+    ("SM-S911", "Samsung", "Galaxy S23"),
+    ("SM-S916", "Samsung", "Galaxy S23+"),
+    ("SM-S918", "Samsung", "Galaxy S23 Ultra"),
+    ("SM-S711", "Samsung", "Galaxy S23 FE"),
+    ("SM-S901", "Samsung", "Galaxy S22"),
+    ("SM-S906", "Samsung", "Galaxy S22+"),
+    ("SM-S908", "Samsung", "Galaxy S22 Ultra"),
+    ("SM-G991", "Samsung", "Galaxy S21"),
+    ("SM-G996", "Samsung", "Galaxy S21+"),
+    ("SM-G998", "Samsung", "Galaxy S21 Ultra"),
+    ("SM-G990", "Samsung", "Galaxy S21 FE"),
+    ("SM-G980", "Samsung", "Galaxy S20"),
+    ("SM-G981", "Samsung", "Galaxy S20 5G"),
+    ("SM-G985", "Samsung", "Galaxy S20+"),
+    ("SM-G986", "Samsung", "Galaxy S20+ 5G"),
+    ("SM-G988", "Samsung", "Galaxy S20 Ultra"),
+    ("SM-G780", "Samsung", "Galaxy S20 FE"),
+    ("SM-G781", "Samsung", "Galaxy S20 FE 5G"),
+    ("SM-G970", "Samsung", "Galaxy S10e"),
+    ("SM-G973", "Samsung", "Galaxy S10"),
+    ("SM-G975", "Samsung", "Galaxy S10+"),
+    ("SM-G960", "Samsung", "Galaxy S9"),
+    ("SM-G965", "Samsung", "Galaxy S9+"),
+    ("SM-G950", "Samsung", "Galaxy S8"),
+    ("SM-G955", "Samsung", "Galaxy S8+"),
+    ("SM-G930", "Samsung", "Galaxy S7"),
+    ("SM-G935", "Samsung", "Galaxy S7 edge"),
+    ("GT-I9500", "Samsung", "Galaxy S4"),
+    ("GT-I9505", "Samsung", "Galaxy S4"),
+    ("GT-I9300", "Samsung", "Galaxy S III"),
+    // Samsung Galaxy Note and Z
+    ("SM-N980", "Samsung", "Galaxy Note20"),
+    ("SM-N981", "Samsung", "Galaxy Note20 5G"),
+    ("SM-N985", "Samsung", "Galaxy Note20 Ultra"),
+    ("SM-N986", "Samsung", "Galaxy Note20 Ultra 5G"),
+    ("SM-N970", "Samsung", "Galaxy Note10"),
+    ("SM-N975", "Samsung", "Galaxy Note10+"),
+    ("SM-N960", "Samsung", "Galaxy Note9"),
+    ("SM-N950", "Samsung", "Galaxy Note8"),
+    ("GT-N7100", "Samsung", "Galaxy Note II"),
+    ("SM-F946", "Samsung", "Galaxy Z Fold5"),
+    ("SM-F936", "Samsung", "Galaxy Z Fold4"),
+    ("SM-F926", "Samsung", "Galaxy Z Fold3 5G"),
+    ("SM-F731", "Samsung", "Galaxy Z Flip5"),
+    ("SM-F721", "Samsung", "Galaxy Z Flip4"),
+    ("SM-F711", "Samsung", "Galaxy Z Flip3 5G"),
+    // Samsung Galaxy A and M
+    ("SM-A556", "Samsung", "Galaxy A55 5G"),
+    ("SM-A546", "Samsung", "Galaxy A54 5G"),
+    ("SM-A536", "Samsung", "Galaxy A53 5G"),
+    ("SM-A528", "Samsung", "Galaxy A52s 5G"),
+    ("SM-A526", "Samsung", "Galaxy A52 5G"),
+    ("SM-A525", "Samsung", "Galaxy A52"),
+    ("SM-A516", "Samsung", "Galaxy A51 5G"),
+    ("SM-A515", "Samsung", "Galaxy A51"),
+    ("SM-A505", "Samsung", "Galaxy A50"),
+    ("SM-A346", "Samsung", "Galaxy A34 5G"),
+    ("SM-A336", "Samsung", "Galaxy A33 5G"),
+    ("SM-A326", "Samsung", "Galaxy A32 5G"),
+    ("SM-A325", "Samsung", "Galaxy A32"),
+    ("SM-A245", "Samsung", "Galaxy A24"),
+    ("SM-A236", "Samsung", "Galaxy A23 5G"),
+    ("SM-A235", "Samsung", "Galaxy A23"),
+    ("SM-A226", "Samsung", "Galaxy A22 5G"),
+    ("SM-A225", "Samsung", "Galaxy A22"),
+    ("SM-A217", "Samsung", "Galaxy A21s"),
+    ("SM-A146", "Samsung", "Galaxy A14 5G"),
+    ("SM-A145", "Samsung", "Galaxy A14"),
+    ("SM-A136", "Samsung", "Galaxy A13 5G"),
+    ("SM-A135", "Samsung", "Galaxy A13"),
+    ("SM-A125", "Samsung", "Galaxy A12"),
+    ("SM-A047", "Samsung", "Galaxy A04s"),
+    ("SM-A035", "Samsung", "Galaxy A03"),
+    ("SM-A032", "Samsung", "Galaxy A03 Core"),
+    ("SM-A736", "Samsung", "Galaxy A73 5G"),
+    ("SM-A725", "Samsung", "Galaxy A72"),
+    ("SM-A715", "Samsung", "Galaxy A71"),
+    ("SM-M336", "Samsung", "Galaxy M33 5G"),
+    ("SM-M325", "Samsung", "Galaxy M32"),
+    ("SM-M315", "Samsung", "Galaxy M31"),
+    // Samsung Galaxy Tab
+    ("SM-X910", "Samsung", "Galaxy Tab S9 Ultra"),
+    ("SM-X810", "Samsung", "Galaxy Tab S9+"),
+    ("SM-X710", "Samsung", "Galaxy Tab S9"),
+    ("SM-X900", "Samsung", "Galaxy Tab S8 Ultra"),
+    ("SM-X800", "Samsung", "Galaxy Tab S8+"),
+    ("SM-X706", "Samsung", "Galaxy Tab S8 5G"),
+    ("SM-X700", "Samsung", "Galaxy Tab S8"),
+    ("SM-X200", "Samsung", "Galaxy Tab A8"),
+    ("SM-X205", "Samsung", "Galaxy Tab A8"),
+    ("SM-T970", "Samsung", "Galaxy Tab S7+"),
+    ("SM-T870", "Samsung", "Galaxy Tab S7"),
+    ("SM-T875", "Samsung", "Galaxy Tab S7"),
+    ("SM-T860", "Samsung", "Galaxy Tab S6"),
+    ("SM-T720", "Samsung", "Galaxy Tab S5e"),
+    ("SM-P610", "Samsung", "Galaxy Tab S6 Lite"),
+    ("SM-P615", "Samsung", "Galaxy Tab S6 Lite"),
+    ("SM-T500", "Samsung", "Galaxy Tab A7"),
+    ("SM-T505", "Samsung", "Galaxy Tab A7"),
+    ("SM-T220", "Samsung", "Galaxy Tab A7 Lite"),
+    ("SM-T225", "Samsung", "Galaxy Tab A7 Lite"),
+    ("SM-T510", "Samsung", "Galaxy Tab A 10.1 (2019)"),
+    ("GT-P5210", "Samsung", "Galaxy Tab 3 10.1"),
+    // Realme
+    ("RMX2061", "Realme", "Realme 6 Pro"),
+    ("RMX2001", "Realme", "Realme 6"),
+    ("RMX2020", "Realme", "Realme C3"),
+    ("RMX2185", "Realme", "Realme C11"),
+    ("RMX2170", "Realme", "Realme 7 Pro"),
+    ("RMX2155", "Realme", "Realme 7"),
+    ("RMX3085", "Realme", "Realme 8"),
+    ("RMX3081", "Realme", "Realme 8 Pro"),
+    ("RMX1971", "Realme", "Realme 5 Pro"),
+    ("RMX1911", "Realme", "Realme 5"),
+    // OnePlus
+    ("CPH2581", "OnePlus", "OnePlus 12"),
+    ("CPH2449", "OnePlus", "OnePlus 11"),
+    ("NE2213", "OnePlus", "OnePlus 10 Pro"),
+    ("LE2123", "OnePlus", "OnePlus 9 Pro"),
+    ("LE2113", "OnePlus", "OnePlus 9"),
+    ("KB2003", "OnePlus", "OnePlus 8T"),
+    ("IN2023", "OnePlus", "OnePlus 8 Pro"),
+    ("IN2013", "OnePlus", "OnePlus 8"),
+    ("AC2003", "OnePlus", "OnePlus Nord"),
+    ("HD1913", "OnePlus", "OnePlus 7T Pro"),
+    ("HD1903", "OnePlus", "OnePlus 7T"),
+    ("GM1913", "OnePlus", "OnePlus 7 Pro"),
+    // OPPO
+    ("CPH1909", "OPPO", "OPPO A5s"),
+    ("CPH2127", "OPPO", "OPPO A53"),
+    // Xiaomi
+    ("M2101K6G", "Xiaomi", "Redmi Note 10 Pro"),
+    // Huawei
+    ("ELE-", "Huawei", "P30"),
+    ("VOG-", "Huawei", "P30 Pro"),
+    ("MAR-", "Huawei", "P30 lite"),
+    ("ANE-", "Huawei", "P20 lite"),
+    ("LYA-", "Huawei", "Mate 20 Pro"),
+    // Amazon Fire tablets, as listed in Amazon's Fire tablet device specifications
+    ("KFRAWI", "Amazon", "Fire HD 8 (2022)"),
+    ("KFQUWI", "Amazon", "Fire 7 (2022)"),
+    ("KFTRPWI", "Amazon", "Fire HD 10 Plus (2021)"),
+    ("KFTRWI", "Amazon", "Fire HD 10 (2021)"),
+    ("KFONWI", "Amazon", "Fire HD 8 (2020)"),
+    // The 9th generation Fire HD 10; the Fire HD 8 of that period is KFKAWI
+    ("KFMAWI", "Amazon", "Fire HD 10 (2019)"),
+    ("KFMUWI", "Amazon", "Fire 7 (2019)"),
+    ("KFKAWI", "Amazon", "Fire HD 8 (2018)"),
+    ("KFSUWI", "Amazon", "Fire HD 10 (2017)"),
+    ("KFDOWI", "Amazon", "Fire HD 8 (2017)"),
+    ("KFAUWI", "Amazon", "Fire 7 (2017)"),
+    ("KFGIWI", "Amazon", "Fire HD 8 (2016)"),
+    ("KFTT", "Amazon", "Kindle Fire HD 7 (2012)"),
+    ("KFOT", "Amazon", "Kindle Fire (2012)"),
+    // Amazon Fire TV
+    ("AFTKA", "Amazon", "Fire TV Stick 4K Max"),
+    ("AFTMM", "Amazon", "Fire TV Stick 4K"),
+    ("AFTSSS", "Amazon", "Fire TV Stick (3rd Gen)"),
+    ("AFTT", "Amazon", "Fire TV Stick (2nd Gen)"),
+    // Meta
+    ("Quest Pro", "Meta", "Quest Pro"),
+    ("Quest 3", "Meta", "Quest 3"),
+    ("Quest 2", "Meta", "Quest 2"),
+    ("Quest", "Meta", "Quest"),
+    ("Pacific", "Meta", "Oculus Go"),
+];
+
+/// Looks up the brand and marketing name of an Android model code
+pub(crate) fn lookup(code: &str) -> Option<(&'static str, &'static str)> {
+    let code = code.to_ascii_lowercase();
+    ANDROID_MODELS
+        .iter()
+        .filter(|(prefix, _, _)| code.starts_with(&prefix.to_ascii_lowercase()))
+        .max_by_key(|(prefix, _, _)| prefix.len())
+        .map(|(_, brand, name)| (*brand, *name))
+}
@@ -0,0 +1,230 @@
+//! Apple hardware identifiers mapped to marketing names.
+//!
+//! Native apps and in-app browsers report the hardware identifier (e.g. `iPhone14,3`
+//! in `FBDV/iPhone14,3`) instead of the generic `iPhone` that Safari sends.
+
+use crate::DeviceType;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum AppleDeviceClass {
+    IPhone,
+    IPad,
+    IPod,
+    Watch,
+    AppleTV,
+    Vision,
+}
+
+impl AppleDeviceClass {
+    /// The device type every member of this class belongs to
+    pub fn device_type(&self) -> DeviceType {
+        match self {
+            AppleDeviceClass::IPhone | AppleDeviceClass::IPod => DeviceType::Mobile,
+            AppleDeviceClass::IPad => DeviceType::Tablet,
+            AppleDeviceClass::Watch => DeviceType::Smartwatch,
+            AppleDeviceClass::AppleTV => DeviceType::TV,
+            AppleDeviceClass::Vision => DeviceType::VRHeadset,
+        }
+    }
+
+    /// The generic name used when an identifier is not in the table yet
+    fn generic_name(&self) -> &'static str {
+        match self {
+            AppleDeviceClass::IPhone => "iPhone",
+            AppleDeviceClass::IPad => "iPad",
+            AppleDeviceClass::IPod => "iPod touch",
+            AppleDeviceClass::Watch => "Apple Watch",
+            AppleDeviceClass::AppleTV => "Apple TV",
+            AppleDeviceClass::Vision => "Apple Vision",
+        }
+    }
+}
+
+/// An Apple device resolved from its hardware identifier
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct AppleModel {
+    pub identifier: String,
+    pub name: &'static str,
+    pub class: AppleDeviceClass,
+}
+
+/// `(hardware identifiers, marketing name)`
+const APPLE_MODELS: &[(&[&str], &str)] = &[
+    // iPhone
+    (&["iPhone1,1"], "iPhone"),
+    (&["iPhone1,2"], "iPhone 3G"),
+    (&["iPhone2,1"], "iPhone 3GS"),
+    (&["iPhone3,1", "iPhone3,2", "iPhone3,3"], "iPhone 4"),
+    (&["iPhone4,1"], "iPhone 4S"),
+    (&["iPhone5,1", "iPhone5,2"], "iPhone 5"),
+    (&["iPhone5,3", "iPhone5,4"], "iPhone 5c"),
+    (&["iPhone6,1", "iPhone6,2"], "iPhone 5s"),
+    (&["iPhone7,2"], "iPhone 6"),
+    (&["iPhone7,1"], "iPhone 6 Plus"),
+    (&["iPhone8,1"], "iPhone 6s"),
+    (&["iPhone8,2"], "iPhone 6s Plus"),
+    (&["iPhone8,4"], "iPhone SE"),
+    (&["iPhone9,1", "iPhone9,3"], "iPhone 7"),
+    (&["iPhone9,2", "iPhone9,4"], "iPhone 7 Plus"),
+    (&["iPhone10,1", "iPhone10,4"], "iPhone 8"),
+    (&["iPhone10,2", "iPhone10,5"], "iPhone 8 Plus"),
+    (&["iPhone10,3", "iPhone10,6"], "iPhone X"),
+    (&["iPhone11,2"], "iPhone XS"),
+    (&["iPhone11,4", "iPhone11,6"], "iPhone XS Max"),
+    (&["iPhone11,8"], "iPhone XR"),
+    (&["iPhone12,1"], "iPhone 11"),
+    (&["iPhone12,3"], "iPhone 11 Pro"),
+    (&["iPhone12,5"], "iPhone 11 Pro Max"),
+    (&["iPhone12,8"], "iPhone SE (2nd generation)"),
+    (&["iPhone13,1"], "iPhone 12 mini"),
+    (&["iPhone13,2"], "iPhone 12"),
+    (&["iPhone13,3"], "iPhone 12 Pro"),
+    (&["iPhone13,4"], "iPhone 12 Pro Max"),
+    (&["iPhone14,4"], "iPhone 13 mini"),
+    (&["iPhone14,5"], "iPhone 13"),
+    (&["iPhone14,2"], "iPhone 13 Pro"),
+    (&["iPhone14,3"], "iPhone 13 Pro Max"),
+    (&["iPhone14,6"], "iPhone SE (3rd generation)"),
+    (&["iPhone14,7"], "iPhone 14"),
+    (&["iPhone14,8"], "iPhone 14 Plus"),
+    (&["iPhone15,2"], "iPhone 14 Pro"),
+    (&["iPhone15,3"], "iPhone 14 Pro Max"),
+    (&["iPhone15,4"], "iPhone 15"),
+    (&["iPhone15,5"], "iPhone 15 Plus"),
+    (&["iPhone16,1"], "iPhone 15 Pro"),
+    (&["iPhone16,2"], "iPhone 15 Pro Max"),
+    (&["iPhone17,3"], "iPhone 16"),
+    (&["iPhone17,4"], "iPhone 16 Plus"),
+    (&["iPhone17,1"], "iPhone 16 Pro"),
+    (&["iPhone17,2"], "iPhone 16 Pro Max"),
+    (&["iPhone17,5"], "iPhone 16e"),
+    // iPad
+    (&["iPad1,1"], "iPad"),
+    (&["iPad2,1", "iPad2,2", "iPad2,3", "iPad2,4"], "iPad 2"),
+    (&["iPad3,1", "iPad3,2", "iPad3,3"], "iPad (3rd generation)"),
+    (&["iPad3,4", "iPad3,5", "iPad3,6"], "iPad (4th generation)"),
+    (&["iPad6,11", "iPad6,12"], "iPad (5th generation)"),
+    (&["iPad7,5", "iPad7,6"], "iPad (6th generation)"),
+    (&["iPad7,11", "iPad7,12"], "iPad (7th generation)"),
+    (&["iPad11,6", "iPad11,7"], "iPad (8th generation)"),
+    (&["iPad12,1", "iPad12,2"], "iPad (9th generation)"),
+    (&["iPad13,18", "iPad13,19"], "iPad (10th generation)"),
+    (&["iPad2,5", "iPad2,6", "iPad2,7"], "iPad mini"),
+    (&["iPad4,4", "iPad4,5", "iPad4,6"], "iPad mini 2"),
+    (&["iPad4,7", "iPad4,8", "iPad4,9"], "iPad mini 3"),
+    (&["iPad5,1", "iPad5,2"], "iPad mini 4"),
+    (&["iPad11,1", "iPad11,2"], "iPad mini (5th generation)"),
+    (&["iPad14,1", "iPad14,2"], "iPad mini (6th generation)"),
+    (&["iPad4,1", "iPad4,2", "iPad4,3"], "iPad Air"),
+    (&["iPad5,3", "iPad5,4"], "iPad Air 2"),
+    (&["iPad11,3", "iPad11,4"], "iPad Air (3rd generation)"),
+    (&["iPad13,1", "iPad13,2"], "iPad Air (4th generation)"),
+    (&["iPad13,16", "iPad13,17"], "iPad Air (5th generation)"),
+    (&["iPad14,8", "iPad14,9"], "iPad Air 11-inch (M2)"),
+    (&["iPad14,10", "iPad14,11"], "iPad Air 13-inch (M2)"),
+    (&["iPad6,3", "iPad6,4"], "iPad Pro (9.7-inch)"),
+    (&["iPad6,7", "iPad6,8"], "iPad Pro (12.9-inch)"),
+    (&["iPad7,1", "iPad7,2"], "iPad Pro (12.9-inch) (2nd generation)"),
+    (&["iPad7,3", "iPad7,4"], "iPad Pro (10.5-inch)"),
+    (&["iPad8,1", "iPad8,2", "iPad8,3", "iPad8,4"], "iPad Pro (11-inch)"),
+    (&["iPad8,5", "iPad8,6", "iPad8,7", "iPad8,8"], "iPad Pro (12.9-inch) (3rd generation)"),
+    (&["iPad8,9", "iPad8,10"], "iPad Pro (11-inch) (2nd generation)"),
+    (&["iPad8,11", "iPad8,12"], "iPad Pro (12.9-inch) (4th generation)"),
+    (&["iPad13,4", "iPad13,5", "iPad13,6", "iPad13,7"], "iPad Pro (11-inch) (3rd generation)"),
+    (&["iPad13,8", "iPad13,9", "iPad13,10", "iPad13,11"], "iPad Pro (12.9-inch) (5th generation)"),
+    (&["iPad14,3", "iPad14,4"], "iPad Pro (11-inch) (4th generation)"),
+    (&["iPad14,5", "iPad14,6"], "iPad Pro (12.9-inch) (6th generation)"),
+    (&["iPad16,3", "iPad16,4"], "iPad Pro 11-inch (M4)"),
+    (&["iPad16,5", "iPad16,6"], "iPad Pro 13-inch (M4)"),
+    // iPod touch
+    (&["iPod1,1"], "iPod touch"),
+    (&["iPod2,1"], "iPod touch (2nd generation)"),
+    (&["iPod3,1"], "iPod touch (3rd generation)"),
+    (&["iPod4,1"], "iPod touch (4th generation)"),
+    (&["iPod5,1"], "iPod touch (5th generation)"),
+    (&["iPod7,1"], "iPod touch (6th generation)"),
+    (&["iPod9,1"], "iPod touch (7th generation)"),
+    // Apple Watch
+    (&["Watch1,1", "Watch1,2"], "Apple Watch"),
+    (&["Watch2,6", "Watch2,7"], "Apple Watch Series 1"),
+    (&["Watch2,3", "Watch2,4"], "Apple Watch Series 2"),
+    (&["Watch3,1", "Watch3,2", "Watch3,3", "Watch3,4"], "Apple Watch Series 3"),
+    (&["Watch4,1", "Watch4,2", "Watch4,3", "Watch4,4"], "Apple Watch Series 4"),
+    (&["Watch5,1", "Watch5,2", "Watch5,3", "Watch5,4"], "Apple Watch Series 5"),
+    (&["Watch5,9", "Watch5,10", "Watch5,11", "Watch5,12"], "Apple Watch SE"),
+    (&["Watch6,1", "Watch6,2", "Watch6,3", "Watch6,4"], "Apple Watch Series 6"),
+    (&["Watch6,6", "Watch6,7", "Watch6,8", "Watch6,9"], "Apple Watch Series 7"),
+    (&["Watch6,10", "Watch6,11", "Watch6,12", "Watch6,13"], "Apple Watch SE (2nd generation)"),
+    (&["Watch6,14", "Watch6,15", "Watch6,16", "Watch6,17"], "Apple Watch Series 8"),
+    (&["Watch6,18"], "Apple Watch Ultra"),
+    (&["Watch7,1", "Watch7,2", "Watch7,3", "Watch7,4"], "Apple Watch Series 9"),
+    (&["Watch7,5"], "Apple Watch Ultra 2"),
+    // Apple TV
+    (&["AppleTV2,1"], "Apple TV (2nd generation)"),
+    (&["AppleTV3,1", "AppleTV3,2"], "Apple TV (3rd generation)"),
+    (&["AppleTV5,3"], "Apple TV HD"),
+    (&["AppleTV6,2"], "Apple TV 4K"),
+    (&["AppleTV11,1"], "Apple TV 4K (2nd generation)"),
+    (&["AppleTV14,1"], "Apple TV 4K (3rd generation)"),
+    // Apple Vision
+    (&["RealityDevice14,1"], "Apple Vision Pro"),
+];
+
+impl AppleModel {
+    /// Resolves a hardware identifier such as `iPhone14,3`
+    ///
+    /// Identifiers that follow Apple's `<class><major>,<minor>` naming scheme but are
+    /// not in the table yet resolve to the generic name of their class (e.g. `iPhone`).
+    /// A bare class name such as `iPhone` is not an identifier.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use agent_parser_ro::{AppleDeviceClass, AppleModel};
+    ///
+    /// let model = AppleModel::lookup("iPhone14,3").unwrap();
+    /// assert_eq!(model.name, "iPhone 13 Pro Max");
+    /// assert_eq!(model.class, AppleDeviceClass::IPhone);
+    /// ```
+    pub fn lookup(identifier: &str) -> Option<AppleModel> {
+        let numbered = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        let (prefix, minor) = identifier.rsplit_once(',')?;
+        let class_name = prefix.trim_end_matches(|c: char| c.is_ascii_digit());
+        if !numbered(&prefix[class_name.len()..]) || !numbered(minor) {
+            return None;
+        }
+        let class = match class_name {
+            "iPhone" => AppleDeviceClass::IPhone,
+            "iPad" => AppleDeviceClass::IPad,
+            "iPod" => AppleDeviceClass::IPod,
+            "Watch" => AppleDeviceClass::Watch,
+            "AppleTV" => AppleDeviceClass::AppleTV,
+            "RealityDevice" => AppleDeviceClass::Vision,
+            _ => return None,
+        };
+        let name = APPLE_MODELS
+            .iter()
+            .find(|(identifiers, _)| identifiers.contains(&identifier))
+            .map_or(class.generic_name(), |(_, name)| *name);
+        Some(AppleModel {
+            identifier: identifier.to_string(),
+            name,
+            class,
+        })
+    }
+
+    /// Finds the first hardware identifier mentioned in `ua`
+    pub(crate) fn find(ua: &str) -> Option<AppleModel> {
+        lazy_static! {
+            static ref IDENTIFIER_REGEX: Regex =
+                Regex::new(r"\b(?:iPhone|iPad|iPod|Watch|AppleTV|RealityDevice)\d+,\d+\b").unwrap();
+        }
+
+        IDENTIFIER_REGEX
+            .find_iter(ua)
+            .find_map(|m| AppleModel::lookup(m.as_str()))
+    }
+}
@@ -0,0 +1,64 @@
+//! Headless browsers, automation frameworks and synthetic testing services.
+//!
+//! These clients run a real browser engine, so they also parse as an ordinary
+//! browser. The signals listed here tell synthetic sessions apart from people.
+//!
+//! Signals are independent of bot detection. Lighthouse, PageSpeed Insights and
+//! GTmetrix are also known bots, so their user agents report both a `bot` of the
+//! `Monitoring` category and an automation signal.
+
+use crate::token::Scope::{Comment, Product};
+use crate::token::{Haystack, Keywords, Scope};
+use serde::{Deserialize, Serialize};
+
+/// A sign that a browser is driven by software rather than a person
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Automation {
+    /// Chrome in headless mode, the default for Puppeteer and Playwright
+    HeadlessChrome,
+    PhantomJS,
+    Selenium,
+    WebDriver,
+    Puppeteer,
+    Playwright,
+    Cypress,
+    /// The jsdom browser emulation for Node.js
+    Jsdom,
+    /// Lighthouse audits, including those run by PageSpeed Insights
+    Lighthouse,
+    PageSpeedInsights,
+    GTmetrix,
+    WebPageTest,
+}
+
+/// `(keywords, signal)`
+#[rustfmt::skip]
+const AUTOMATION_TOKENS: &[(&[(&str, Scope)], Automation)] = &[
+    (&[("headlesschrome", Product)], Automation::HeadlessChrome),
+    (&[("phantomjs", Product)], Automation::PhantomJS),
+    (&[("selenium", Product)], Automation::Selenium),
+    (&[("webdriver", Product)], Automation::WebDriver),
+    (&[("puppeteer", Product)], Automation::Puppeteer),
+    (&[("playwright", Product)], Automation::Playwright),
+    (&[("cypress", Product)], Automation::Cypress),
+    (&[("jsdom", Product)], Automation::Jsdom),
+    (&[("chrome-lighthouse", Product), ("lighthouse", Product)], Automation::Lighthouse),
+    (&[("page speed insights", Comment)], Automation::PageSpeedInsights),
+    (&[("gtmetrix", Product), ("gtmetrix", Comment)], Automation::GTmetrix),
+    (&[("webpagetest", Product), ("ptst", Product)], Automation::WebPageTest),
+];
+
+impl Automation {
+    /// Lists the automation signals in the user agent in order of appearance, each once
+    pub(crate) fn detect(haystack: &Haystack) -> Vec<Automation> {
+        let mut found: Vec<(usize, Automation)> = AUTOMATION_TOKENS
+            .iter()
+            .filter_map(|&(keywords, signal)| {
+                let (_, span) = Keywords(keywords).find(haystack)?;
+                Some((span.start, signal))
+            })
+            .collect();
+        found.sort_by_key(|&(start, _)| start);
+        found.into_iter().map(|(_, signal)| signal).collect()
+    }
+}
@@ -0,0 +1,472 @@
+//! Known crawlers, fetchers and other automated clients.
+//!
+//! Bots name themselves with a product token (`facebookexternalhit/1.1`) or a
+//! comment entry (`(compatible; Googlebot/2.1; +http://www.google.com/bot.html)`).
+//! Tokens are compared whole and case-insensitively against the table, except for
+//! tokens with spaces, dots or underscores, which are searched for anywhere.
+
+use crate::token::{Haystack, Token};
+use crate::{Confidence, Evidence, Source, Version};
+use BotCategory::*;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// What a bot is for
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum BotCategory {
+    /// Crawls pages for a web search index
+    SearchEngine,
+    /// Fetches a page to render a link preview in a chat or social app
+    SocialPreview,
+    /// Crawls for backlink, ranking or site audit data
+    SeoTool,
+    /// Checks uptime or performance
+    Monitoring,
+    /// Polls RSS and Atom feeds
+    FeedFetcher,
+    /// Preserves pages for a web archive
+    Archiver,
+    /// Collects content to train AI models or to index it for AI-powered search
+    ///
+    /// `Google-Extended` and `Applebot-Extended` are robots.txt tokens only. Their
+    /// crawls arrive as Googlebot and Applebot, so no user agent carries them.
+    AiCrawler,
+    /// Fetches a page on demand because a user asked an AI assistant about it
+    AiAssistant,
+    /// Any other crawler, including unknown clients that call themselves bots
+    Scraper,
+}
+
+/// A crawler or other automated client
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+pub struct Bot {
+    /// The bot's name, such as `Googlebot Smartphone` or `AhrefsBot`
+    pub name: String,
+    /// The company or project running the bot, when known
+    pub operator: Option<String>,
+    pub category: BotCategory,
+    pub version: Option<Version>,
+    /// The information page linked from the user agent, such as
+    /// `http://www.google.com/bot.html`
+    pub url: Option<String>,
+    /// The product token the bot obeys in robots.txt, such as `GPTBot`
+    pub robots_token: Option<String>,
+}
+
+/// Bots whose robots.txt token differs from the token in their user agent
+const ROBOTS_TOKENS: &[(&str, &str)] = &[("yahoo! slurp", "Slurp")];
+
+/// `(token, name, operator, category)`
+#[rustfmt::skip]
+const KNOWN_BOTS: &[(&str, &str, &str, BotCategory)] = &[
+    // Google
+    ("googlebot", "Googlebot", "Google", SearchEngine),
+    ("googlebot-image", "Googlebot Image", "Google", SearchEngine),
+    ("googlebot-news", "Googlebot News", "Google", SearchEngine),
+    ("googlebot-video", "Googlebot Video", "Google", SearchEngine),
+    ("storebot-google", "Storebot-Google", "Google", SearchEngine),
+    ("google-inspectiontool", "Google-InspectionTool", "Google", SearchEngine),
+    ("googleother", "GoogleOther", "Google", SearchEngine),
+    ("googleother-image", "GoogleOther Image", "Google", SearchEngine),
+    ("googleother-video", "GoogleOther Video", "Google", SearchEngine),
+    ("adsbot-google", "AdsBot-Google", "Google", SearchEngine),
+    ("adsbot-google-mobile", "AdsBot-Google-Mobile", "Google", SearchEngine),
+    ("mediapartners-google", "Mediapartners-Google", "Google", SearchEngine),
+    ("apis-google", "APIs-Google", "Google", SearchEngine),
+    ("duplexweb-google", "DuplexWeb-Google", "Google", SearchEngine),
+    ("google-safety", "Google-Safety", "Google", SearchEngine),
+    ("feedfetcher-google", "FeedFetcher-Google", "Google", FeedFetcher),
+    ("feedburner", "FeedBurner", "Google", FeedFetcher),
+    ("google-site-verification", "Google Site Verification", "Google", SeoTool),
+    ("chrome-lighthouse", "Lighthouse", "Google", Monitoring),
+    ("google page speed insights", "PageSpeed Insights", "Google", Monitoring),
+    ("google web preview", "Google Web Preview", "Google", SocialPreview),
+    // Microsoft
+    ("bingbot", "Bingbot", "Microsoft", SearchEngine),
+    ("bingpreview", "BingPreview", "Microsoft", SearchEngine),
+    ("msnbot", "MSNBot", "Microsoft", SearchEngine),
+    ("msnbot-media", "MSNBot Media", "Microsoft", SearchEngine),
+    ("adidxbot", "AdIdxBot", "Microsoft", SearchEngine),
+    ("microsoftpreview", "MicrosoftPreview", "Microsoft", SocialPreview),
+    ("skypeuripreview", "SkypeUriPreview", "Microsoft", SocialPreview),
+    // Other search engines
+    ("yahoo! slurp", "Yahoo! Slurp", "Yahoo", SearchEngine),
+    ("duckduckbot", "DuckDuckBot", "DuckDuckGo", SearchEngine),
+    ("duckduckgo-favicons-bot", "DuckDuckGo Favicons Bot", "DuckDuckGo", SearchEngine),
+    ("baiduspider", "Baiduspider", "Baidu", SearchEngine),
+    ("baiduspider-image", "Baiduspider Image", "Baidu", SearchEngine),
+    ("baiduspider-render", "Baiduspider Render", "Baidu", SearchEngine),
+    ("yandexbot", "YandexBot", "Yandex", SearchEngine),
+    ("yandeximages", "YandexImages", "Yandex", SearchEngine),
+    ("yandexvideo", "YandexVideo", "Yandex", SearchEngine),
+    ("yandexmobilebot", "YandexMobileBot", "Yandex", SearchEngine),
+    ("yandexaccessibilitybot", "YandexAccessibilityBot", "Yandex", SearchEngine),
+    ("yandexrenderresourcesbot", "YandexRenderResourcesBot", "Yandex", SearchEngine),
+    ("yandexfavicons", "YandexFavicons", "Yandex", SearchEngine),
+    ("yandexdirect", "YandexDirect", "Yandex", SearchEngine),
+    ("yandexnews", "YandexNews", "Yandex", FeedFetcher),
+    ("yandexwebmaster", "YandexWebmaster", "Yandex", SeoTool),
+    ("yandexmetrika", "YandexMetrika", "Yandex", Monitoring),
+    ("applebot", "Applebot", "Apple", SearchEngine),
+    ("applenewsbot", "AppleNewsBot", "Apple", FeedFetcher),
+    ("sogou web spider", "Sogou Web Spider", "Sogou", SearchEngine),
+    ("sogou inst spider", "Sogou Inst Spider", "Sogou", SearchEngine),
+    ("360spider", "360Spider", "Qihoo 360", SearchEngine),
+    ("haosouspider", "HaosouSpider", "Qihoo 360", SearchEngine),
+    ("yisouspider", "YisouSpider", "Alibaba", SearchEngine),
+    ("petalbot", "PetalBot", "Huawei", SearchEngine),
+    ("seznambot", "SeznamBot", "Seznam", SearchEngine),
+    ("mojeekbot", "MojeekBot", "Mojeek", SearchEngine),
+    ("qwantify", "Qwantify", "Qwant", SearchEngine),
+    ("qwantbot", "Qwantbot", "Qwant", SearchEngine),
+    ("exabot", "Exabot", "Exalead", SearchEngine),
+    ("yeti", "Yeti", "Naver", SearchEngine),
+    ("daumoa", "Daumoa", "Kakao", SearchEngine),
+    ("coccocbot-web", "coccocbot", "Coc Coc", SearchEngine),
+    ("coccocbot-image", "coccocbot Image", "Coc Coc", SearchEngine),
+    ("mail.ru_bot", "Mail.RU_Bot", "Mail.Ru", SearchEngine),
+    ("neevabot", "Neevabot", "Neeva", SearchEngine),
+    ("seekportbot", "SeekportBot", "Seekport", SearchEngine),
+    ("yacybot", "YaCy", "YaCy", SearchEngine),
+    ("marginalia", "Marginalia", "Marginalia Search", SearchEngine),
+    ("bravebot", "Bravebot", "Brave", SearchEngine),
+    ("startpagebot", "StartpageBot", "Startpage", SearchEngine),
+    ("ecosiabot", "EcosiaBot", "Ecosia", SearchEngine),
+    // Social previews and messengers
+    ("facebookexternalhit", "Facebook External Hit", "Meta", SocialPreview),
+    ("facebookcatalog", "Facebook Catalog", "Meta", SocialPreview),
+    ("whatsapp", "WhatsApp", "Meta", SocialPreview),
+    ("twitterbot", "Twitterbot", "X", SocialPreview),
+    ("linkedinbot", "LinkedInBot", "LinkedIn", SocialPreview),
+    ("pinterestbot", "Pinterestbot", "Pinterest", SocialPreview),
+    ("slackbot", "Slackbot", "Slack", SocialPreview),
+    ("slackbot-linkexpanding", "Slackbot Link Expanding", "Slack", SocialPreview),
+    ("slack-imgproxy", "Slack Image Proxy", "Slack", SocialPreview),
+    ("discordbot", "Discordbot", "Discord", SocialPreview),
+    ("telegrambot", "TelegramBot", "Telegram", SocialPreview),
+    ("redditbot", "redditbot", "Reddit", SocialPreview),
+    ("embedly", "Embedly", "Embedly", SocialPreview),
+    ("iframely", "Iframely", "Itteco", SocialPreview),
+    ("vkshare", "vkShare", "VK", SocialPreview),
+    ("bitlybot", "bitlybot", "Bitly", SocialPreview),
+    ("flipboardproxy", "FlipboardProxy", "Flipboard", SocialPreview),
+    ("quora link preview", "Quora Link Preview", "Quora", SocialPreview),
+    ("snap url preview service", "Snap URL Preview Service", "Snap", SocialPreview),
+    ("kakaotalk-scrap", "KakaoTalk Scrap", "Kakao", SocialPreview),
+    ("mastodon", "Mastodon", "Mastodon", SocialPreview),
+    ("bluesky", "Bluesky Cardyb", "Bluesky", SocialPreview),
+    ("xing-contenttabreceiver", "XING Content Tab Receiver", "XING", SocialPreview),
+    ("nuzzel", "Nuzzel", "Nuzzel", SocialPreview),
+    ("outbrain", "Outbrain", "Outbrain", SocialPreview),
+    ("google-pagerenderer", "Google PageRenderer", "Google", SocialPreview),
+    // SEO tools
+    ("ahrefsbot", "AhrefsBot", "Ahrefs", SeoTool),
+    ("ahrefssiteaudit", "AhrefsSiteAudit", "Ahrefs", SeoTool),
+    ("semrushbot", "SemrushBot", "Semrush", SeoTool),
+    ("semrushbot-ba", "SemrushBot Backlink Audit", "Semrush", SeoTool),
+    ("semrushbot-si", "SemrushBot Site Intelligence", "Semrush", SeoTool),
+    ("siteauditbot", "SiteAuditBot", "Semrush", SeoTool),
+    ("splitsignalbot", "SplitSignalBot", "Semrush", SeoTool),
+    ("mj12bot", "MJ12bot", "Majestic", SeoTool),
+    ("dotbot", "DotBot", "Moz", SeoTool),
+    ("rogerbot", "rogerbot", "Moz", SeoTool),
+    ("blexbot", "BLEXBot", "WebMeUp", SeoTool),
+    ("serpstatbot", "serpstatbot", "Serpstat", SeoTool),
+    ("dataforseobot", "DataForSeoBot", "DataForSEO", SeoTool),
+    ("screaming frog seo spider", "Screaming Frog SEO Spider", "Screaming Frog", SeoTool),
+    ("sitebulb", "Sitebulb", "Sitebulb", SeoTool),
+    ("seokicks", "SEOkicks", "SEOkicks", SeoTool),
+    ("barkrowler", "Barkrowler", "Babbar", SeoTool),
+    ("megaindex.ru", "MegaIndex", "MegaIndex", SeoTool),
+    ("linkdexbot", "linkdexbot", "Linkdex", SeoTool),
+    ("sistrix crawler", "SISTRIX Crawler", "SISTRIX", SeoTool),
+    ("oncrawl", "OnCrawl", "OnCrawl", SeoTool),
+    ("contentking", "ContentKing", "Conductor", SeoTool),
+    ("seobilitybot", "SeobilityBot", "Seobility", SeoTool),
+    ("linkpadbot", "LinkpadBot", "Linkpad", SeoTool),
+    ("rytebot", "RyteBot", "Ryte", SeoTool),
+    ("siteimprovebot", "SiteimproveBot", "Siteimprove", SeoTool),
+    ("spbot", "spbot", "OpenLinkProfiler", SeoTool),
+    ("ia_archiver", "Alexa Crawler", "Amazon", SeoTool),
+    ("seositecheckup", "SEO Site Checkup", "SEO Site Checkup", SeoTool),
+    ("woorankreview", "WooRank", "WooRank", SeoTool),
+    ("deepcrawl", "DeepCrawl", "Lumar", SeoTool),
+    ("seolyt", "Seolyt", "Seolyt", SeoTool),
+    ("netpeakspider", "Netpeak Spider", "Netpeak", SeoTool),
+    // Monitoring
+    ("uptimerobot", "UptimeRobot", "UptimeRobot", Monitoring),
+    ("pingdom.com_bot", "Pingdom", "SolarWinds", Monitoring),
+    ("site24x7", "Site24x7", "Zoho", Monitoring),
+    ("statuscake", "StatusCake", "StatusCake", Monitoring),
+    ("statuscake_pagespeed_indev", "StatusCake PageSpeed", "StatusCake", Monitoring),
+    ("newrelicpinger", "NewRelicPinger", "New Relic", Monitoring),
+    ("datadog agent", "Datadog Agent", "Datadog", Monitoring),
+    ("datadogsynthetics", "Datadog Synthetics", "Datadog", Monitoring),
+    ("better uptime bot", "Better Uptime Bot", "Better Stack", Monitoring),
+    ("freshping", "Freshping", "Freshworks", Monitoring),
+    ("hetrixtools", "HetrixTools", "HetrixTools", Monitoring),
+    ("gtmetrix", "GTmetrix", "GTmetrix", Monitoring),
+    ("uptime-kuma", "Uptime Kuma", "Uptime Kuma", Monitoring),
+    ("updown.io daemon", "updown.io", "updown.io", Monitoring),
+    ("nodeping", "NodePing", "NodePing", Monitoring),
+    ("catchpoint", "Catchpoint", "Catchpoint", Monitoring),
+    ("ruxitsynthetic", "Dynatrace Synthetic", "Dynatrace", Monitoring),
+    ("checkly", "Checkly", "Checkly", Monitoring),
+    ("zabbix", "Zabbix", "Zabbix", Monitoring),
+    ("check_http", "Nagios check_http", "Nagios", Monitoring),
+    ("blackbox exporter", "Prometheus Blackbox Exporter", "Prometheus", Monitoring),
+    ("w3c_validator", "W3C Validator", "W3C", Monitoring),
+    ("w3c-checklink", "W3C Link Checker", "W3C", Monitoring),
+    ("netcraftsurveyagent", "NetcraftSurveyAgent", "Netcraft", Monitoring),
+    ("monitis", "Monitis", "Monitis", Monitoring),
+    ("pingbot", "Pingbot", "Pingbot", Monitoring),
+    ("site-shot", "Site-Shot", "Site-Shot", Monitoring),
+    ("sucuri", "Sucuri", "GoDaddy", Monitoring),
+    ("cloudflare-alwaysonline", "Cloudflare Always Online", "Cloudflare", Monitoring),
+    ("cloudflare-healthchecks", "Cloudflare Health Checks", "Cloudflare", Monitoring),
+    ("elb-healthchecker", "ELB Health Checker", "Amazon", Monitoring),
+    ("googlestackdrivermonitoring-uptimechecks", "Cloud Monitoring Uptime Checks", "Google", Monitoring),
+    ("kube-probe", "Kubernetes Probe", "Kubernetes", Monitoring),
+    ("mediatoolkitbot", "Mediatoolkit", "Mediatoolkit", Monitoring),
+    // Feed fetchers
+    ("feedly", "Feedly", "Feedly", FeedFetcher),
+    ("inoreader", "Inoreader", "Inoreader", FeedFetcher),
+    ("newsblur feed fetcher", "NewsBlur", "NewsBlur", FeedFetcher),
+    ("feedbin", "Feedbin", "Feedbin", FeedFetcher),
+    ("theoldreader.com", "The Old Reader", "The Old Reader", FeedFetcher),
+    ("tiny tiny rss", "Tiny Tiny RSS", "Tiny Tiny RSS", FeedFetcher),
+    ("netnewswire", "NetNewsWire", "NetNewsWire", FeedFetcher),
+    ("feedspot", "Feedspot", "Feedspot", FeedFetcher),
+    ("bloglovin", "Bloglovin", "Bloglovin", FeedFetcher),
+    ("miniflux", "Miniflux", "Miniflux", FeedFetcher),
+    ("freshrss", "FreshRSS", "FreshRSS", FeedFetcher),
+    ("superfeedr", "Superfeedr", "Superfeedr", FeedFetcher),
+    ("feedvalidator", "Feed Validator", "W3C", FeedFetcher),
+    ("feedbot", "Feedbot", "Feedbot", FeedFetcher),
+    ("feedpress", "FeedPress", "FeedPress", FeedFetcher),
+    ("newsgator", "NewsGator", "NewsGator", FeedFetcher),
+    ("rssowl", "RSSOwl", "RSSOwl", FeedFetcher),
+    ("liferea", "Liferea", "Liferea", FeedFetcher),
+    // Archivers
+    ("archive.org_bot", "archive.org_bot", "Internet Archive", Archiver),
+    ("special_archiver", "special_archiver", "Internet Archive", Archiver),
+    ("heritrix", "Heritrix", "Internet Archive", Archiver),
+    ("archiveteam archivebot", "ArchiveBot", "ArchiveTeam", Archiver),
+    ("arquivo-web-crawler", "Arquivo Web Crawler", "Arquivo.pt", Archiver),
+    ("archive.today", "archive.today", "archive.today", Archiver),
+    ("webcitation", "WebCite", "WebCite", Archiver),
+    ("perma.cc", "Perma.cc", "Harvard Library", Archiver),
+    ("bnf.fr_bot", "BnF Bot", "Bibliotheque nationale de France", Archiver),
+    ("nlnz_iaharvester", "NLNZ Harvester", "National Library of New Zealand", Archiver),
+    // AI crawlers and assistant fetchers
+    ("gptbot", "GPTBot", "OpenAI", AiCrawler),
+    ("chatgpt-user", "ChatGPT-User", "OpenAI", AiAssistant),
+    ("oai-searchbot", "OAI-SearchBot", "OpenAI", AiCrawler),
+    ("claudebot", "ClaudeBot", "Anthropic", AiCrawler),
+    ("claude-web", "Claude-Web", "Anthropic", AiCrawler),
+    ("claude-user", "Claude-User", "Anthropic", AiAssistant),
+    ("claude-searchbot", "Claude-SearchBot", "Anthropic", AiCrawler),
+    ("anthropic-ai", "anthropic-ai", "Anthropic", AiCrawler),
+    ("ccbot", "CCBot", "Common Crawl", AiCrawler),
+    ("perplexitybot", "PerplexityBot", "Perplexity", AiCrawler),
+    ("perplexity-user", "Perplexity-User", "Perplexity", AiAssistant),
+    ("bytespider", "Bytespider", "ByteDance", AiCrawler),
+    ("amazonbot", "Amazonbot", "Amazon", AiCrawler),
+    ("cohere-ai", "cohere-ai", "Cohere", AiCrawler),
+    ("cohere-training-data-crawler", "cohere-training-data-crawler", "Cohere", AiCrawler),
+    ("diffbot", "Diffbot", "Diffbot", AiCrawler),
+    ("youbot", "YouBot", "You.com", AiCrawler),
+    ("omgili", "omgili", "Webz.io", AiCrawler),
+    ("omgilibot", "omgilibot", "Webz.io", AiCrawler),
+    ("facebookbot", "FacebookBot", "Meta", AiCrawler),
+    ("meta-externalagent", "Meta-ExternalAgent", "Meta", AiCrawler),
+    ("meta-externalfetcher", "Meta-ExternalFetcher", "Meta", AiAssistant),
+    ("imagesiftbot", "ImagesiftBot", "Hive", AiCrawler),
+    ("timpibot", "Timpibot", "Timpi", AiCrawler),
+    ("ai2bot", "AI2Bot", "Allen Institute for AI", AiCrawler),
+    ("duckassistbot", "DuckAssistBot", "DuckDuckGo", AiCrawler),
+    ("mistralai-user", "MistralAI-User", "Mistral AI", AiAssistant),
+    ("iaskspider", "iAskSpider", "iAsk", AiCrawler),
+    ("velenpublicwebcrawler", "VelenPublicWebCrawler", "Velen", AiCrawler),
+    ("kangaroo bot", "Kangaroo Bot", "Kangaroo LLM", AiCrawler),
+    ("pangubot", "PanguBot", "Huawei", AiCrawler),
+    ("google-cloudvertexbot", "Google-CloudVertexBot", "Google", AiCrawler),
+    ("google-notebooklm", "Google-NotebookLM", "Google", AiAssistant),
+    ("novaact", "NovaAct", "Amazon", AiAssistant),
+    // Other crawlers and scrapers
+    ("scrapy", "Scrapy", "Zyte", Scraper),
+    ("nutch", "Apache Nutch", "Apache", Scraper),
+    ("crawler4j", "crawler4j", "crawler4j", Scraper),
+    ("httrack", "HTTrack", "HTTrack", Scraper),
+    ("mauibot", "MauiBot", "MauiBot", Scraper),
+    ("dataprovider.com", "Dataprovider", "Dataprovider.com", Scraper),
+    ("netestate ne crawler", "netEstate NE Crawler", "netEstate", Scraper),
+    ("zoominfobot", "ZoomInfoBot", "ZoomInfo", Scraper),
+    ("magpie-crawler", "magpie-crawler", "Brandwatch", Scraper),
+    ("panscient.com", "panscient", "Panscient", Scraper),
+    ("turnitinbot", "TurnitinBot", "Turnitin", Scraper),
+    ("proximic", "proximic", "Comscore", Scraper),
+    ("grapeshotcrawler", "GrapeshotCrawler", "Oracle", Scraper),
+    ("gumgum-bot", "GumGum Bot", "GumGum", Scraper),
+    ("ltx71", "ltx71", "ltx71", Scraper),
+    ("trendictionbot", "trendictionbot", "Talkwalker", Scraper),
+    ("linguee bot", "Linguee Bot", "DeepL", Scraper),
+    ("nimbostratus-bot", "Nimbostratus-Bot", "Cloud System Networks", Scraper),
+    ("admantx", "ADmantX", "ADmantX", Scraper),
+    ("bidswitchbot", "BidSwitchBot", "BidSwitch", Scraper),
+    ("mixrankbot", "MixrankBot", "MixRank", Scraper),
+    ("linkfluence", "Linkfluence", "Meltwater", Scraper),
+    ("awariobot", "AwarioBot", "Awario", Scraper),
+    ("awariosmartbot", "AwarioSmartBot", "Awario", Scraper),
+    ("censysinspect", "CensysInspect", "Censys", Scraper),
+    ("internetmeasurement", "InternetMeasurement", "Driftnet", Scraper),
+    ("bubing", "BUbiNG", "BUbiNG", Scraper),
+    ("cliqzbot", "Cliqzbot", "Cliqz", Scraper),
+    ("wotbox", "Wotbox", "Wotbox", Scraper),
+    ("ezooms", "Ezooms", "Ezooms", Scraper),
+    ("sitelockspider", "SiteLockSpider", "SiteLock", Scraper),
+    ("seekbot", "Seekbot", "Seekbot", Scraper),
+    ("brandverity", "BrandVerity", "BrandVerity", Scraper),
+    ("ioncrawl", "IonCrawl", "IonCrawl", Scraper),
+    ("keybot translation-search-machine", "Keybot", "Keybot", Scraper),
+    ("amzn-searchbot", "Amzn-SearchBot", "Amazon", Scraper),
+    ("paperlibot", "PaperLiBot", "Paper.li", Scraper),
+    ("cincraw", "Cincraw", "Cincraw", Scraper),
+    ("sidetrade indexer bot", "Sidetrade Indexer Bot", "Sidetrade", Scraper),
+    ("pandalytics", "Pandalytics", "Domain Data Group", Scraper),
+    ("webwikibot", "WebwikiBot", "Webwiki", Scraper),
+    ("hubspot crawler", "HubSpot Crawler", "HubSpot", Scraper),
+    ("yextbot", "Yextbot", "Yext", Scraper),
+];
+
+impl Bot {
+    /// Detects a known bot in `ua`, or an unknown client naming itself a bot, crawler
+    /// or spider in a `name/version` token
+    pub(crate) fn detect(haystack: &Haystack) -> Option<(Bot, Evidence)> {
+        type Entry = (&'static str, &'static str, &'static str, BotCategory);
+        lazy_static! {
+            // Whole tokens are looked up by name, the rest searched for in the text
+            static ref BY_TOKEN: HashMap<&'static str, &'static Entry> =
+                KNOWN_BOTS.iter().map(|entry| (entry.0, entry)).collect();
+            static ref SEARCHED: Vec<&'static Entry> = KNOWN_BOTS
+                .iter()
+                .filter(|(token, ..)| token.contains([' ', '.', '_']))
+                .collect();
+        }
+
+        let (ua, lower, tokens) = (haystack.ua(), haystack.lower(), haystack.tokens());
+        let candidates = candidates(tokens);
+
+        let known = candidates
+            .iter()
+            .find_map(|(_, version, span)| {
+                BY_TOKEN
+                    .get(&lower[span.clone()])
+                    .map(|&entry| (entry, *version, span.clone()))
+            })
+            .or_else(|| {
+                SEARCHED.iter().find_map(|&entry| {
+                    let start = lower.find(entry.0)?;
+                    let version = ua[start + entry.0.len()..]
+                        .strip_prefix('/')
+                        .and_then(|rest| rest.split([' ', ';', ')', '(']).next());
+                    Some((entry, version, start..start + entry.0.len()))
+                })
+            });
+
+        let (bot, confidence, span) = match known {
+            Some((&(token, name, operator, category), version, span)) => {
+                let robots_token = match ROBOTS_TOKENS.iter().find(|(known, _)| *known == token) {
+                    Some((_, robots_token)) => robots_token,
+                    None => &ua[span.clone()],
+                };
+                // Google runs the same crawler with a phone and a desktop user agent
+                let name = match token {
+                    "googlebot" if lower.contains("mobile") => "Googlebot Smartphone",
+                    "googlebot" => "Googlebot Desktop",
+                    _ => name,
+                };
+                let bot = Bot {
+                    name: name.to_string(),
+                    operator: Some(operator.to_string()),
+                    category,
+                    version: version.and_then(Version::parse),
+                    url: info_url(ua, tokens),
+                    robots_token: Some(robots_token.to_string()),
+                };
+                (bot, Confidence::High, span)
+            }
+            None => {
+                let (name, version, span) = candidates.into_iter().find(|(_, version, span)| {
+                    let name = &lower[span.clone()];
+                    version.is_some()
+                        && ["bot", "crawler", "spider"]
+                            .iter()
+                            .any(|suffix| name.ends_with(suffix))
+                })?;
+                let bot = Bot {
+                    name: name.to_string(),
+                    operator: None,
+                    category: Scraper,
+                    version: version.and_then(Version::parse),
+                    url: info_url(ua, tokens),
+                    robots_token: Some(name.to_string()),
+                };
+                (bot, Confidence::Medium, span)
+            }
+        };
+        let evidence = Evidence::matched(ua, span, Source::Bot, confidence);
+        Some((bot, evidence))
+    }
+}
+
+/// Names that could identify a bot, with their versions and spans: product names and
+/// the `name/version` entries of comments
+fn candidates<'a>(tokens: &[Token<'a>]) -> Vec<(&'a str, Option<&'a str>, Range<usize>)> {
+    let mut candidates = Vec::new();
+    for token in tokens {
+        match token {
+            Token::Product(product) => {
+                let start = product.span.start;
+                candidates.push((
+                    product.name,
+                    product.version,
+                    start..start + product.name.len(),
+                ));
+            }
+            Token::Comment(comment) => {
+                for (entry, span) in comment.entries() {
+                    let (name, version) = match entry.split_once('/') {
+                        Some((name, version)) => (name.trim(), Some(version.trim())),
+                        None => (entry, None),
+                    };
+                    candidates.push((name, version, span.start..span.start + name.len()));
+                }
+            }
+        }
+    }
+    candidates
+}
+
+/// The first `http://` or `https://` address inside a comment, or given as a bare
+/// `+http...` product as some AI fetchers do, without the leading `+`
+fn info_url(ua: &str, tokens: &[Token]) -> Option<String> {
+    tokens.iter().find_map(|token| {
+        let text = match token {
+            Token::Comment(comment) => comment.text,
+            Token::Product(product) => ua[product.span.clone()].trim_start_matches('+'),
+        };
+        text.match_indices("http")
+            .take_while(|(start, _)| *start == 0 || matches!(token, Token::Comment(_)))
+            .map(|(start, _)| {
+                text[start..]
+                    .split([' ', ';', ')', '(', ','])
+                    .next()
+                    .unwrap_or("")
+            })
+            .find(|url| url.starts_with("http://") || url.starts_with("https://"))
+            .map(str::to_string)
+    })
+}
@@ -0,0 +1,490 @@
+//! User-Agent Client Hints (`Sec-CH-UA-*` request headers).
+//!
+//! Header values are RFC 8941 structured fields. Only the subset used by client
+//! hints is parsed: lists of strings with parameters, strings and booleans.
+
+use crate::device::android_device;
+use crate::frozen::is_reduced_version;
+use crate::{
+    Architecture, Browser, DeviceType, Engine, Evidence, OperatingSystem, UserAgentInfo, Version,
+};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Raw `Sec-CH-UA-*` header values as received from the client
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Default)]
+pub struct ClientHints {
+    /// `Sec-CH-UA`
+    pub ua: Option<String>,
+    /// `Sec-CH-UA-Full-Version-List`
+    pub full_version_list: Option<String>,
+    /// `Sec-CH-UA-Platform`
+    pub platform: Option<String>,
+    /// `Sec-CH-UA-Platform-Version`
+    pub platform_version: Option<String>,
+    /// `Sec-CH-UA-Mobile`
+    pub mobile: Option<String>,
+    /// `Sec-CH-UA-Model`
+    pub model: Option<String>,
+    /// `Sec-CH-UA-Arch`
+    pub arch: Option<String>,
+    /// `Sec-CH-UA-Bitness`
+    pub bitness: Option<String>,
+    /// `Sec-CH-UA-WoW64`
+    pub wow64: Option<String>,
+    /// `Sec-CH-UA-Form-Factors`
+    pub form_factors: Option<String>,
+}
+
+/// A brand entry from `Sec-CH-UA` or `Sec-CH-UA-Full-Version-List`
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+pub struct Brand {
+    pub brand: String,
+    pub version: String,
+}
+
+impl Brand {
+    /// Whether this is a GREASE brand such as `"Not_A Brand"`, which Chromium adds
+    /// to keep servers from relying on the list order
+    pub fn is_grease(&self) -> bool {
+        lazy_static! {
+            static ref GREASE_REGEX: Regex =
+                Regex::new(r"(?i)^\s*not[^a-z]*a[^a-z]*brand\s*$").unwrap();
+        }
+        GREASE_REGEX.is_match(&self.brand)
+    }
+}
+
+impl ClientHints {
+    /// Collects client hints from request headers, matching names case-insensitively
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use agent_parser_ro::ClientHints;
+    ///
+    /// let hints = ClientHints::from_headers([
+    ///     ("sec-ch-ua", r#""Chromium";v="120", "Google Chrome";v="120""#),
+    ///     ("sec-ch-ua-platform", r#""Windows""#),
+    /// ]);
+    /// assert_eq!(hints.platform.as_deref(), Some(r#""Windows""#));
+    /// ```
+    pub fn from_headers<'a, I>(headers: I) -> ClientHints
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut hints = ClientHints::default();
+        for (name, value) in headers {
+            let field = match name.to_ascii_lowercase().as_str() {
+                "sec-ch-ua" => &mut hints.ua,
+                "sec-ch-ua-full-version-list" => &mut hints.full_version_list,
+                "sec-ch-ua-platform" => &mut hints.platform,
+                "sec-ch-ua-platform-version" => &mut hints.platform_version,
+                "sec-ch-ua-mobile" => &mut hints.mobile,
+                "sec-ch-ua-model" => &mut hints.model,
+                "sec-ch-ua-arch" => &mut hints.arch,
+                "sec-ch-ua-bitness" => &mut hints.bitness,
+                "sec-ch-ua-wow64" => &mut hints.wow64,
+                "sec-ch-ua-form-factors" => &mut hints.form_factors,
+                _ => continue,
+            };
+            *field = Some(value.to_string());
+        }
+        hints
+    }
+
+    /// Brands from `Sec-CH-UA-Full-Version-List`, falling back to `Sec-CH-UA`, with
+    /// GREASE brands removed
+    pub fn brands(&self) -> Vec<Brand> {
+        self.full_version_list
+            .as_deref()
+            .or(self.ua.as_deref())
+            .map(parse_brand_list)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|brand| !brand.is_grease())
+            .collect()
+    }
+
+    /// Decodes the structured header values
+    pub(crate) fn values(&self) -> HintValues {
+        let string = |value: &Option<String>| value.as_deref().and_then(parse_string);
+        HintValues {
+            brands: self.brands(),
+            platform: string(&self.platform),
+            platform_version: string(&self.platform_version),
+            mobile: self.mobile.as_deref().and_then(parse_boolean),
+            model: string(&self.model),
+            arch: string(&self.arch),
+            bitness: string(&self.bitness),
+            wow64: self.wow64.as_deref().and_then(parse_boolean),
+            form_factors: self
+                .form_factors
+                .as_deref()
+                .map(parse_string_list)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The high-entropy values returned by `navigator.userAgentData.getHighEntropyValues()`
+///
+/// Deserializes from the JSON the browser produces, so a frontend can post it
+/// alongside `navigator.userAgent`.
+///
+/// # Example
+///
+/// ```
+/// use agent_parser_ro::UserAgentData;
+///
+/// let data: UserAgentData = serde_json::from_str(r#"{
+///     "brands": [{"brand": "Chromium", "version": "120"}],
+///     "mobile": false,
+///     "platform": "macOS",
+///     "platformVersion": "14.1.0"
+/// }"#).unwrap();
+/// assert_eq!(data.platform_version.as_deref(), Some("14.1.0"));
+/// ```
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UserAgentData {
+    pub brands: Vec<Brand>,
+    pub full_version_list: Vec<Brand>,
+    pub platform: Option<String>,
+    pub platform_version: Option<String>,
+    pub model: Option<String>,
+    pub mobile: Option<bool>,
+    pub architecture: Option<String>,
+    pub bitness: Option<String>,
+    pub wow64: Option<bool>,
+    pub form_factors: Vec<String>,
+}
+
+impl UserAgentData {
+    /// Normalizes the payload into the same values header-based hints decode to
+    pub(crate) fn values(&self) -> HintValues {
+        let brands = match self.full_version_list.is_empty() {
+            true => &self.brands,
+            false => &self.full_version_list,
+        };
+        HintValues {
+            brands: brands
+                .iter()
+                .filter(|brand| !brand.is_grease())
+                .cloned()
+                .collect(),
+            platform: self.platform.clone(),
+            platform_version: self.platform_version.clone(),
+            mobile: self.mobile,
+            model: self.model.clone(),
+            arch: self.architecture.clone(),
+            bitness: self.bitness.clone(),
+            wow64: self.wow64,
+            form_factors: self.form_factors.clone(),
+        }
+    }
+}
+
+/// Client hints decoded from either headers or a `userAgentData` payload
+pub(crate) struct HintValues {
+    brands: Vec<Brand>,
+    platform: Option<String>,
+    platform_version: Option<String>,
+    mobile: Option<bool>,
+    model: Option<String>,
+    arch: Option<String>,
+    bitness: Option<String>,
+    wow64: Option<bool>,
+    form_factors: Vec<String>,
+}
+
+impl HintValues {
+    /// Overrides the UA-derived fields of `info` with whatever the hints know better
+    pub(crate) fn apply(self, info: &mut UserAgentInfo) {
+        let non_empty = |value: Option<String>| value.filter(|v| !v.is_empty());
+
+        if let Some((browser, brand)) = self
+            .brands
+            .iter()
+            .find_map(|brand| brand_browser(&brand.brand).map(|browser| (browser, brand)))
+        {
+            info.browser = browser;
+            info.evidence.browser = Some(Evidence::hint(&brand.brand));
+            if let Some(version) = Version::parse(&brand.version) {
+                info.frozen.browser_version = is_reduced_version(&version);
+                info.browser_version = Some(version);
+            }
+        }
+        if let Some(chromium) = self.brands.iter().find(|brand| brand.brand == "Chromium") {
+            info.engine = Engine::Blink;
+            if let Some(version) = Version::parse(&chromium.version) {
+                info.frozen.engine_version = is_reduced_version(&version);
+                info.engine_version = Some(version);
+            }
+        }
+
+        if let Some((os, platform)) = self
+            .platform
+            .as_deref()
+            .and_then(|platform| platform_os(platform).map(|os| (os, platform)))
+        {
+            info.os = os;
+            info.evidence.os = Some(Evidence::hint(platform));
+        }
+        if let Some(version) = self.platform_version.as_deref().and_then(Version::parse) {
+            (info.os_version, info.os_release) = match info.os {
+                // Windows 10 and 11 both send `Windows NT 10.0`, only the hint tells them apart
+                OperatingSystem::Windows => match version.major {
+                    13.. => (Version::parse("10.0"), Some("11".to_string())),
+                    1..=12 => (Version::parse("10.0"), Some("10".to_string())),
+                    0 => (info.os_version.take(), info.os_release.take()),
+                },
+                _ => (Some(version), None),
+            };
+            info.frozen.os_version = false;
+            info.frozen.os_version_at_least = None;
+        }
+
+        match self.mobile {
+            Some(true) if matches!(info.device_type, DeviceType::Unknown | DeviceType::Desktop) => {
+                info.device_type = DeviceType::Mobile;
+                info.evidence.device_type = Some(Evidence::hint("?1"));
+            }
+            Some(false)
+                if info.os == OperatingSystem::Android
+                    && info.device_type == DeviceType::Mobile =>
+            {
+                info.device_type = DeviceType::Tablet;
+                info.evidence.device_type = Some(Evidence::hint("?0"));
+            }
+            _ => {}
+        }
+        // Form factors are more specific than the mobile flag
+        if let Some((device_type, form_factor)) = self
+            .form_factors
+            .iter()
+            .find_map(|f| form_factor_device(f).map(|device_type| (device_type, f)))
+        {
+            info.device_type = device_type;
+            info.evidence.device_type = Some(Evidence::hint(form_factor));
+        }
+
+        if let Some(model) = non_empty(self.model) {
+            info.device = android_device(&model);
+            info.frozen.device_model = false;
+        }
+
+        let is_64bit = non_empty(self.bitness).map(|b| b == "64");
+        if let Some(arch) = non_empty(self.arch) {
+            let architecture = match (arch.to_ascii_lowercase().as_str(), is_64bit) {
+                ("x86", Some(true)) => Architecture::X86_64,
+                ("x86", _) => Architecture::X86,
+                ("arm", Some(true)) => Architecture::Arm64,
+                ("arm", _) => Architecture::Arm,
+                _ => Architecture::Unknown,
+            };
+            if architecture != Architecture::Unknown {
+                info.architecture = architecture;
+            }
+        }
+        if is_64bit.is_some() {
+            info.is_64bit = is_64bit;
+        }
+        // A 32-bit browser under WOW64 still runs on a 64-bit machine
+        if self.wow64 == Some(true) {
+            info.architecture = Architecture::X86_64;
+            info.is_64bit = Some(true);
+        }
+    }
+}
+
+/// Maps a client hint brand onto a browser, `None` for Chromium and unknown brands
+fn brand_browser(brand: &str) -> Option<Browser> {
+    match brand {
+        "Google Chrome" | "HeadlessChrome" => Some(Browser::Chrome),
+        "Microsoft Edge" => Some(Browser::Edge),
+        "Opera" | "Opera GX" | "Opera Mobile" => Some(Browser::Opera),
+        "Brave" => Some(Browser::Brave),
+        "Yandex" | "YaBrowser" => Some(Browser::Yandex),
+        "Vivaldi" => Some(Browser::Vivaldi),
+        "Samsung Internet" => Some(Browser::SamsungBrowser),
+        "DuckDuckGo" => Some(Browser::DuckDuckGo),
+        "Android WebView" => Some(Browser::WebView),
+        _ => None,
+    }
+}
+
+/// Maps a `Sec-CH-UA-Form-Factors` entry onto a device type
+fn form_factor_device(form_factor: &str) -> Option<DeviceType> {
+    match form_factor {
+        "Desktop" => Some(DeviceType::Desktop),
+        "Mobile" => Some(DeviceType::Mobile),
+        "Tablet" => Some(DeviceType::Tablet),
+        "XR" => Some(DeviceType::VRHeadset),
+        "Watch" => Some(DeviceType::Smartwatch),
+        "Automotive" => Some(DeviceType::CarSystem),
+        _ => None,
+    }
+}
+
+/// Maps a `Sec-CH-UA-Platform` value onto an operating system
+fn platform_os(platform: &str) -> Option<OperatingSystem> {
+    match platform {
+        "Windows" => Some(OperatingSystem::Windows),
+        "macOS" => Some(OperatingSystem::MacOS),
+        "Android" => Some(OperatingSystem::Android),
+        "iOS" => Some(OperatingSystem::IOS),
+        "Chrome OS" | "ChromeOS" => Some(OperatingSystem::ChromeOS),
+        "Linux" => Some(OperatingSystem::Linux),
+        _ => None,
+    }
+}
+
+/// Parses an sf-list of strings carrying a `v` parameter, e.g.
+/// `"Chromium";v="120", "Not_A Brand";v="8"`
+fn parse_brand_list(input: &str) -> Vec<Brand> {
+    let mut parser = Parser::new(input);
+    let mut brands = Vec::new();
+    loop {
+        parser.skip_whitespace();
+        if parser.is_done() {
+            break;
+        }
+        let Some(brand) = parser.string() else {
+            break;
+        };
+        let mut version = String::new();
+        while parser.eat(';') {
+            parser.skip_whitespace();
+            let key = parser.key();
+            let value = if parser.eat('=') {
+                parser.bare_item()
+            } else {
+                Some(String::from("?1"))
+            };
+            if key == "v" {
+                version = value.unwrap_or_default();
+            }
+        }
+        brands.push(Brand { brand, version });
+
+        parser.skip_whitespace();
+        if !parser.eat(',') {
+            break;
+        }
+    }
+    brands
+}
+
+/// Parses an sf-list of strings, e.g. `"Desktop", "XR"`
+fn parse_string_list(input: &str) -> Vec<String> {
+    let mut parser = Parser::new(input);
+    let mut items = Vec::new();
+    loop {
+        parser.skip_whitespace();
+        let Some(item) = parser.string() else {
+            break;
+        };
+        items.push(item);
+        parser.skip_whitespace();
+        if !parser.eat(',') {
+            break;
+        }
+    }
+    items
+}
+
+/// Parses an sf-string such as `"Windows"`
+fn parse_string(input: &str) -> Option<String> {
+    let mut parser = Parser::new(input.trim());
+    let value = parser.string()?;
+    parser.is_done().then_some(value)
+}
+
+/// Parses an sf-boolean, `?1` or `?0`
+fn parse_boolean(input: &str) -> Option<bool> {
+    match input.trim() {
+        "?1" => Some(true),
+        "?0" => Some(false),
+        _ => None,
+    }
+}
+
+/// A cursor over a structured field value
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Parser { input, pos: 0 }
+    }
+
+    fn is_done(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        while let Some(c) = self.peek().filter(|c| f(*c)) {
+            self.pos += c.len_utf8();
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn key(&mut self) -> &'a str {
+        self.take_while(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "_-.*".contains(c))
+    }
+
+    fn string(&mut self) -> Option<String> {
+        if !self.eat('"') {
+            return None;
+        }
+        let mut value = String::new();
+        loop {
+            let c = self.peek()?;
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Some(value),
+                '\\' => {
+                    let escaped = self.peek().filter(|c| *c == '"' || *c == '\\')?;
+                    self.pos += 1;
+                    value.push(escaped);
+                }
+                _ => value.push(c),
+            }
+        }
+    }
+
+    /// Reads a string, token, number or boolean, returning its textual value
+    fn bare_item(&mut self) -> Option<String> {
+        match self.peek()? {
+            '"' => self.string(),
+            _ => {
+                let item = self.take_while(|c| !matches!(c, ';' | ',' | ' ' | '\t'));
+                (!item.is_empty()).then(|| item.to_string())
+            }
+        }
+    }
+}
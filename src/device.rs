@@ -0,0 +1,190 @@
+//! Device brand and model detection.
+
+use crate::AppleModel;
+use crate::token::{Haystack, Token};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// The hardware a user agent runs on.
+///
+/// `model_code` is the identifier exactly as it appears in the user agent (e.g.
+/// `SM-A536B`), while `model` is the most readable name known for it.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Default)]
+pub struct Device {
+    pub brand: Option<String>,
+    pub model: Option<String>,
+    pub model_code: Option<String>,
+}
+
+/// Model code prefixes used by Android vendors
+const ANDROID_BRANDS: &[(&str, &str)] = &[
+    ("SM-", "Samsung"),
+    ("GT-", "Samsung"),
+    ("SCH-", "Samsung"),
+    ("SGH-", "Samsung"),
+    ("SAMSUNG", "Samsung"),
+    ("RMX", "Realme"),
+    ("KF", "Amazon"),
+    ("AFT", "Amazon"),
+    ("Quest", "Meta"),
+    ("Pixel", "Google"),
+    ("Nexus", "Google"),
+    ("Redmi", "Xiaomi"),
+    ("POCO", "Xiaomi"),
+    ("Mi ", "Xiaomi"),
+    ("moto", "Motorola"),
+    ("XT", "Motorola"),
+    ("CPH", "OPPO"),
+    ("LM-", "LG"),
+    ("LG-", "LG"),
+    ("HUAWEI", "Huawei"),
+    ("ONEPLUS", "OnePlus"),
+    ("vivo", "vivo"),
+    ("Nokia", "Nokia"),
+    ("SHIELD", "NVIDIA"),
+    ("Lenovo", "Lenovo"),
+    ("ASUS", "ASUS"),
+    ("HTC", "HTC"),
+    ("Infinix", "Infinix"),
+    ("TECNO", "TECNO"),
+    ("Microsoft", "Microsoft"),
+];
+
+impl Device {
+    /// Detects the device brand and model from the user agent
+    pub(crate) fn detect(haystack: &Haystack) -> Device {
+        let ua = haystack.ua();
+        lazy_static! {
+            // Devices named by a dedicated token, with the model either fixed or captured
+            static ref KNOWN_DEVICES: Vec<(Regex, &'static str, Option<&'static str>)> = vec![
+                (Regex::new(r"(?i)\biphone\b").unwrap(), "Apple", Some("iPhone")),
+                (Regex::new(r"(?i)\bipad\b").unwrap(), "Apple", Some("iPad")),
+                (Regex::new(r"(?i)\bipod\b").unwrap(), "Apple", Some("iPod touch")),
+                (Regex::new(r"(?i)\bapple ?tv\b").unwrap(), "Apple", Some("Apple TV")),
+                (Regex::new(r"(?i)\bmacintosh\b").unwrap(), "Apple", Some("Mac")),
+                (Regex::new(r"(?i)\b(?P<model>playstation (?:\d|vita|portable))").unwrap(), "Sony", None),
+                // `(...; Xbox; Xbox One)` names the console twice, most specifically last
+                (Regex::new(r"(?i)\b(?P<model>xbox (?:one|series [xs]))").unwrap(), "Microsoft", None),
+                (Regex::new(r"(?i)\bxbox\b").unwrap(), "Microsoft", Some("Xbox")),
+                (Regex::new(r"(?i)\b(?P<model>nintendo (?:switch|wiiu|wii|3ds|ds))").unwrap(), "Nintendo", None),
+                (Regex::new(r"(?i)\bkindle/").unwrap(), "Amazon", Some("Kindle")),
+                (Regex::new(r"(?i)\bcrkey/").unwrap(), "Google", Some("Chromecast")),
+                (Regex::new(r"(?i)\broku/").unwrap(), "Roku", Some("Roku")),
+                (Regex::new(r"(?i)\btesla/").unwrap(), "Tesla", Some("Tesla")),
+                (Regex::new(r"(?i)\bblackberry ?(?P<code>\d{4})").unwrap(), "BlackBerry", None),
+            ];
+        }
+
+        if let Some(model) = AppleModel::find(ua) {
+            return Device {
+                brand: Some("Apple".to_string()),
+                model: Some(model.name.to_string()),
+                model_code: Some(model.identifier),
+            };
+        }
+
+        for (reg, brand, model) in KNOWN_DEVICES.iter() {
+            if let Some(caps) = reg.captures(ua) {
+                let code = caps.name("code").map(|m| m.as_str().to_string());
+                let model = match model {
+                    Some(model) => model.to_string(),
+                    None => caps
+                        .name("model")
+                        .or(caps.name("code"))
+                        .unwrap()
+                        .as_str()
+                        .to_string(),
+                };
+                return Device {
+                    brand: Some(brand.to_string()),
+                    model: Some(model),
+                    model_code: code,
+                };
+            }
+        }
+
+        match android_model_code(haystack) {
+            Some((vendor, code)) => {
+                let mut device = android_device(code);
+                device.brand = device.brand.or(vendor.map(str::to_string));
+                device
+            }
+            None => Device::default(),
+        }
+    }
+}
+
+/// Extracts the model code from the Android comment, e.g. `SM-A536B` from
+/// `(Linux; Android 13; SM-A536B)` or `RMX2061` from `(...; en-US; RMX2061 Build/...)`
+///
+/// A vendor named in an entry of its own, as in `(Windows Phone 10.0; Android 6.0.1;
+/// Microsoft; Lumia 950)`, is returned alongside the model code.
+fn android_model_code<'a>(haystack: &Haystack<'a>) -> Option<(Option<&'static str>, &'a str)> {
+    lazy_static! {
+        static ref LOCALE_REGEX: Regex = Regex::new(r"^[a-z]{2}(?:[-_][a-zA-Z]{2})?$").unwrap();
+    }
+
+    let lower = haystack.lower();
+    let comment = haystack.tokens().iter().find_map(|token| match token {
+        Token::Comment(comment) if lower[comment.span.clone()].contains("android") => Some(comment),
+        _ => None,
+    })?;
+
+    let mut vendor = None;
+    let code = comment
+        .entries()
+        .map(|(entry, _)| entry)
+        .skip_while(|entry| !entry.to_ascii_lowercase().starts_with("android"))
+        .skip(1)
+        .map(|entry| match entry.find(" Build/") {
+            Some(pos) => entry[..pos].trim(),
+            None => entry,
+        })
+        .find(|entry| {
+            let lower = entry.to_ascii_lowercase();
+            if let Some(brand) = android_brand(entry)
+                && brand.eq_ignore_ascii_case(entry)
+            {
+                vendor = Some(brand);
+                return false;
+            }
+            !entry.is_empty()
+                && !entry.contains('/')
+                && !lower.starts_with("rv:")
+                && !LOCALE_REGEX.is_match(entry)
+                && !matches!(
+                    lower.as_str(),
+                    "u" | "i" | "mobile" | "tablet" | "wv" | "linux"
+                )
+        })?;
+    Some((vendor, code))
+}
+
+/// Describes an Android device from its model code, using the model database when
+/// the `android-models` feature is enabled
+pub(crate) fn android_device(code: &str) -> Device {
+    #[cfg(feature = "android-models")]
+    if let Some((brand, name)) = crate::android_models::lookup(code) {
+        return Device {
+            brand: Some(brand.to_string()),
+            model: Some(name.to_string()),
+            model_code: Some(code.to_string()),
+        };
+    }
+
+    Device {
+        brand: android_brand(code).map(str::to_string),
+        model: Some(code.to_string()),
+        model_code: Some(code.to_string()),
+    }
+}
+
+/// Looks up the vendor of an Android model code by its prefix
+fn android_brand(code: &str) -> Option<&'static str> {
+    let lower = code.to_ascii_lowercase();
+    ANDROID_BRANDS
+        .iter()
+        .find(|(prefix, _)| lower.starts_with(&prefix.to_ascii_lowercase()))
+        .map(|(_, brand)| *brand)
+}
@@ -0,0 +1,55 @@
+//! Errors raised while building a parser.
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A rule pattern is not a valid regular expression or exceeds the size limit
+    InvalidPattern {
+        pattern: String,
+        source: regex::Error,
+    },
+    /// A rule pattern is longer than the configured maximum
+    PatternTooLong { pattern: String, limit: usize },
+    /// An IP range given to a bot verifier is not an address or CIDR block
+    InvalidIpRange { range: String },
+    /// A rule file could not be read
+    Io(std::io::Error),
+    /// A uap-core rule file is not valid YAML or does not have the expected shape
+    #[cfg(feature = "uap")]
+    Yaml(serde_norway::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidPattern { pattern, source } => {
+                write!(f, "invalid rule pattern `{}`: {}", pattern, source)
+            }
+            Error::PatternTooLong { pattern, limit } => write!(
+                f,
+                "rule pattern is {} bytes long, the limit is {}: `{}`",
+                pattern.len(),
+                limit,
+                pattern
+            ),
+            Error::InvalidIpRange { range } => write!(f, "invalid IP range `{}`", range),
+            Error::Io(source) => write!(f, "failed to read rule file: {}", source),
+            #[cfg(feature = "uap")]
+            Error::Yaml(source) => write!(f, "invalid rule file: {}", source),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InvalidPattern { source, .. } => Some(source),
+            Error::PatternTooLong { .. } => None,
+            Error::InvalidIpRange { .. } => None,
+            Error::Io(source) => Some(source),
+            #[cfg(feature = "uap")]
+            Error::Yaml(source) => Some(source),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Why the parser decided on each detected field.
+
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// How far a detection can be trusted
+#[derive(
+    Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Default,
+)]
+pub enum Confidence {
+    /// A guess, such as the desktop fallback for any user agent mentioning Linux
+    #[default]
+    Low,
+    /// A generic token shared by many clients, such as `Chrome/`
+    Medium,
+    /// A token or hint specific to one client, such as `SamsungBrowser/`
+    High,
+}
+
+/// What produced a detection
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+pub enum Source {
+    /// A built-in keyword. Tier 0 holds specific keywords, tier 1 generic ones
+    Keyword { keyword: String, tier: usize },
+    /// The fallback that treats user agents naming Windows, Macintosh or Linux as desktops
+    DesktopFallback,
+    /// An Apple hardware identifier such as `iPad13,18`
+    AppleModel,
+    /// A known crawler, or a token naming itself a bot, crawler or spider
+    Bot,
+    /// User-Agent Client Hints or `navigator.userAgentData`
+    ClientHints,
+    /// An entry of the `[FBAN/...;FBAV/...]` block of Facebook's in-app browser
+    FacebookMetadata,
+    /// The device comment of an Instagram app user agent
+    InstagramMetadata,
+    /// A rule loaded from a uap-core rule file
+    UapRule { regex: String },
+    /// A custom rule given to the parser builder
+    CustomRule { pattern: String },
+}
+
+/// The evidence behind one detected field
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+pub struct Evidence {
+    /// The text that matched, or the hint value that decided
+    pub token: String,
+    /// The byte range of the token in the user agent, `None` for client hints
+    pub span: Option<Range<usize>>,
+    /// What produced the detection
+    pub rule: Source,
+    pub confidence: Confidence,
+}
+
+impl Evidence {
+    /// Evidence for a match on `span` of the user agent
+    pub(crate) fn matched(
+        ua: &str,
+        span: Range<usize>,
+        rule: Source,
+        confidence: Confidence,
+    ) -> Evidence {
+        Evidence {
+            token: ua[span.clone()].to_string(),
+            span: Some(span),
+            rule,
+            confidence,
+        }
+    }
+
+    /// Evidence for a built-in keyword, trusting the specific first tier over the
+    /// generic second one
+    pub(crate) fn keyword(ua: &str, keyword: &str, span: Range<usize>, tier: usize) -> Evidence {
+        let confidence = if tier == 0 {
+            Confidence::High
+        } else {
+            Confidence::Medium
+        };
+        let rule = Source::Keyword {
+            keyword: keyword.to_string(),
+            tier,
+        };
+        Evidence::matched(ua, span, rule, confidence)
+    }
+
+    /// Evidence for a client hint value
+    pub(crate) fn hint(value: &str) -> Evidence {
+        Evidence {
+            token: value.to_string(),
+            span: None,
+            rule: Source::ClientHints,
+            confidence: Confidence::High,
+        }
+    }
+}
+
+/// The evidence behind the `os`, `browser` and `device_type` of a parse result
+///
+/// A field has no evidence when it was not detected.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Default)]
+pub struct DetectionEvidence {
+    pub os: Option<Evidence>,
+    pub browser: Option<Evidence>,
+    pub device_type: Option<Evidence>,
+}
+
+impl DetectionEvidence {
+    /// The lowest confidence across the three fields, counting undetected fields as
+    /// [`Confidence::Low`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use agent_parser_ro::{Confidence, UserAgentParser};
+    ///
+    /// let info = UserAgentParser::parse("Mozilla/5.0 (Linux; Android 13; SM-A536B) AppleWebKit/537.36 (KHTML, like Gecko) SamsungBrowser/21.0 Chrome/110.0.5481.154 Mobile Safari/537.36");
+    /// assert_eq!(info.evidence.browser.unwrap().confidence, Confidence::High);
+    ///
+    /// let info = UserAgentParser::parse("SomeTool/1.0 (Linux)");
+    /// assert_eq!(info.evidence.confidence(), Confidence::Low);
+    /// ```
+    pub fn confidence(&self) -> Confidence {
+        [&self.os, &self.browser, &self.device_type]
+            .into_iter()
+            .map(|evidence| evidence.as_ref().map_or(Confidence::Low, |e| e.confidence))
+            .min()
+            .unwrap_or_default()
+    }
+}
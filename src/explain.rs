@@ -0,0 +1,110 @@
+//! Step-by-step traces of how the built-in rules classified a user agent.
+
+use crate::{Evidence, UserAgentInfo};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The field a trace step decides
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Field {
+    Os,
+    Browser,
+    DeviceType,
+}
+
+/// What a trace step did
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+pub enum Outcome {
+    /// Nothing matched, so the next tier was tried, if any
+    NoMatch,
+    /// A keyword matched but maps to no known value, so the next tier was tried, if
+    /// any
+    Unmapped,
+    /// The field was decided, shown with its `Debug` form, and later tiers were not tried
+    Detected(String),
+    /// The stage did not run because the field was already decided
+    Skipped,
+}
+
+/// One rule tier or fallback tried while parsing
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct TraceStep {
+    pub field: Field,
+    /// The stage that ran, such as `BROWSER_REGEX[0]` or `desktop fallback`
+    pub stage: String,
+    /// What the stage matched, if anything
+    pub matched: Option<Evidence>,
+    pub outcome: Outcome,
+    /// Whether this is the field's last tier, so that no other tier is tried after a
+    /// miss
+    #[serde(default)]
+    pub last: bool,
+}
+
+impl TraceStep {
+    pub(crate) fn new(
+        field: Field,
+        stage: impl Into<String>,
+        matched: Option<Evidence>,
+        detected: Option<String>,
+    ) -> TraceStep {
+        let outcome = match (&matched, detected) {
+            (_, Some(value)) => Outcome::Detected(value),
+            (Some(_), None) => Outcome::Unmapped,
+            (None, None) => Outcome::NoMatch,
+        };
+        TraceStep {
+            field,
+            stage: stage.into(),
+            matched,
+            outcome,
+            last: false,
+        }
+    }
+
+    /// Marks the step as the field's last tier
+    pub(crate) fn last(mut self, last: bool) -> TraceStep {
+        self.last = last;
+        self
+    }
+}
+
+impl fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:?}): ", self.stage, self.field)?;
+        if let Some(matched) = &self.matched {
+            write!(f, "matched {:?}", matched.token)?;
+            if let Some(span) = &matched.span {
+                write!(f, " at {}..{}", span.start, span.end)?;
+            }
+            write!(f, ", ")?;
+        }
+        let next = match self.last {
+            true => "no tiers left",
+            false => "trying the next tier",
+        };
+        match &self.outcome {
+            Outcome::NoMatch => write!(f, "no match, {}", next),
+            Outcome::Unmapped => write!(f, "no known value, {}", next),
+            Outcome::Detected(value) => write!(f, "detected {}", value),
+            Outcome::Skipped => write!(f, "skipped, already decided"),
+        }
+    }
+}
+
+/// The result of [`UserAgentParser::explain`](crate::UserAgentParser::explain): the
+/// parse result and every step that led to it, in order
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Explanation {
+    pub info: UserAgentInfo,
+    pub steps: Vec<TraceStep>,
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{}", step)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+//! The bracketed metadata block of Facebook's in-app browser.
+//!
+//! Facebook apps append `[FBAN/FBIOS;FBAV/441.0;FBDV/iPhone14,3;...]` to the web
+//! view's user agent. Each `KEY/value` entry describes the app or the device, often
+//! more precisely than the rest of the user agent.
+
+use crate::device::android_device;
+use crate::token::Haystack;
+use crate::{
+    AppleModel, Browser, Confidence, Device, DeviceType, Evidence, OperatingSystem, Source,
+    UserAgentInfo, Version,
+};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// The typed content of a Facebook metadata block
+///
+/// # Example
+///
+/// ```
+/// use agent_parser_ro::{DeviceType, UserAgentParser};
+///
+/// let info = UserAgentParser::parse("Mozilla/5.0 (iPhone; CPU iPhone OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Mobile/15E148 [FBAN/FBIOS;FBAV/426.0.0.45.108;FBDV/iPhone14,3;FBMD/iPhone;FBSN/iOS;FBSV/16.6;FBSS/3;FBID/phone;FBLC/en_US;FBOP/5]");
+/// let facebook = info.facebook.unwrap();
+///
+/// assert_eq!(facebook.app_name.as_deref(), Some("FBIOS"));
+/// assert_eq!(facebook.app_version.unwrap().major, 426);
+/// assert_eq!(facebook.device.as_deref(), Some("iPhone14,3"));
+/// assert_eq!(facebook.os_version.unwrap().raw, "16.6");
+/// assert_eq!(facebook.locale.as_deref(), Some("en_US"));
+/// assert_eq!(facebook.form_factor, Some(DeviceType::Mobile));
+/// assert_eq!(facebook.screen_scale, Some(3.0));
+/// ```
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct FacebookMetadata {
+    /// The app's code name from `FBAN` or `FB_IAB`, such as `FBIOS`, `FB4A` or
+    /// `MessengerForiOS`
+    pub app_name: Option<String>,
+    /// `FBAV`
+    pub app_version: Option<Version>,
+    /// The hardware identifier or model code from `FBDV`, such as `iPhone14,3` or
+    /// `SM-G991B`
+    pub device: Option<String>,
+    /// `FBSV`
+    pub os_version: Option<Version>,
+    /// `FBLC`, such as `en_US`
+    pub locale: Option<String>,
+    /// The mobile carrier from `FBCR`, when the device has one
+    pub carrier: Option<String>,
+    /// `FBID`, which names the device a phone or a tablet
+    pub form_factor: Option<DeviceType>,
+    /// The screen's pixel ratio, from `FBSS` on iOS or the density in `FBDM` on Android
+    pub screen_scale: Option<f32>,
+}
+
+/// One `KEY/value` entry of the block, with the byte range of the whole entry
+struct Entry<'a> {
+    key: &'a str,
+    value: &'a str,
+    span: Range<usize>,
+}
+
+/// Splits the first `[FB...]` block of `ua` into its entries
+fn entries(ua: &str) -> Option<Vec<Entry<'_>>> {
+    let start = ua.find("[FB")? + 1;
+    let end = ua[start..].find(']').map_or(ua.len(), |end| start + end);
+    let mut offset = start;
+    let mut entries = Vec::new();
+    for entry in ua[start..end].split(';') {
+        let span = offset..offset + entry.len();
+        offset = span.end + 1;
+        if let Some((key, value)) = entry.split_once('/') {
+            entries.push(Entry {
+                key: key.trim(),
+                value: value.trim(),
+                span,
+            });
+        }
+    }
+    Some(entries)
+}
+
+impl FacebookMetadata {
+    /// Parses the metadata block of the user agent, if it has one
+    pub(crate) fn detect(haystack: &Haystack) -> Option<FacebookMetadata> {
+        let entries = entries(haystack.ua())?;
+        let value = |key: &str| {
+            entries
+                .iter()
+                .find(|entry| entry.key == key && !entry.value.is_empty())
+                .map(|entry| entry.value)
+        };
+        let density = value("FBDM").and_then(|metrics| {
+            metrics
+                .trim_matches(['{', '}'])
+                .split(',')
+                .find_map(|metric| metric.trim().strip_prefix("density="))
+        });
+        Some(FacebookMetadata {
+            app_name: value("FBAN").or(value("FB_IAB")).map(str::to_string),
+            app_version: value("FBAV").and_then(Version::parse),
+            device: value("FBDV").map(str::to_string),
+            os_version: value("FBSV").and_then(Version::parse),
+            locale: value("FBLC").map(str::to_string),
+            carrier: value("FBCR").map(str::to_string),
+            form_factor: value("FBID").and_then(|form| match form.to_ascii_lowercase().as_str() {
+                "phone" => Some(DeviceType::Mobile),
+                "tablet" => Some(DeviceType::Tablet),
+                _ => None,
+            }),
+            screen_scale: value("FBSS")
+                .or(density)
+                .and_then(|scale| scale.parse().ok()),
+        })
+    }
+
+    /// Fills the main fields of `info` from the metadata block of the user agent
+    ///
+    /// The browser becomes [`Browser::Facebook`] and the form factor decides the
+    /// device type. The OS and device are only filled in when the rest of the user
+    /// agent left them out, as reduced Android user agents do.
+    pub(crate) fn apply(&self, haystack: &Haystack, info: &mut UserAgentInfo) {
+        let ua = haystack.ua();
+        let Some(entries) = entries(ua) else {
+            return;
+        };
+        let entry = |key: &str| entries.iter().find(|entry| entry.key == key);
+        let evidence = |key: &str| {
+            entry(key).map(|entry| {
+                let span = entry.span.clone();
+                Evidence::matched(ua, span, Source::FacebookMetadata, Confidence::High)
+            })
+        };
+
+        if let Some(evidence) = evidence("FBAN").or_else(|| evidence("FB_IAB")) {
+            info.browser = Browser::Facebook;
+            info.browser_version = self.app_version.clone();
+            info.evidence.browser = Some(evidence);
+        }
+        if let Some(form_factor) = &self.form_factor
+            && info.device_type != DeviceType::Bot
+        {
+            info.device_type = form_factor.clone();
+            info.evidence.device_type = evidence("FBID");
+        }
+        if info.os == OperatingSystem::Unknown {
+            let os = match entry("FBSN").map(|entry| entry.value) {
+                Some("iOS") => OperatingSystem::IOS,
+                Some("iPadOS") => OperatingSystem::IPadOS,
+                Some("Android") => OperatingSystem::Android,
+                _ => OperatingSystem::Unknown,
+            };
+            if os != OperatingSystem::Unknown {
+                info.os = os;
+                info.evidence.os = evidence("FBSN");
+            }
+        }
+        if info.os_version.is_none() {
+            info.os_version = self.os_version.clone();
+        }
+        // Reduced Android user agents replace the model code with `K`
+        let known_device = info
+            .device
+            .model_code
+            .as_deref()
+            .is_some_and(|code| code != "K");
+        if let Some(code) = &self.device
+            && !known_device
+        {
+            info.device = match AppleModel::lookup(code) {
+                Some(model) => Device {
+                    brand: Some("Apple".to_string()),
+                    model: Some(model.name.to_string()),
+                    model_code: Some(model.identifier),
+                },
+                None => android_device(code),
+            };
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Detection of the placeholder values sent by reduced user agents.
+//!
+//! Chromium's reduced user agent fixes the platform to `Windows NT 10.0`,
+//! `Mac OS X 10_15_7`, `Android 10; K` or `CrOS x86_64 14541.0.0` and zeroes every
+//! version component after the major one. Safari and Firefox cap the macOS version
+//! the same way.
+
+use crate::{Device, OperatingSystem, Version};
+use serde::{Deserialize, Serialize};
+
+/// Fields of a [`UserAgentInfo`](crate::UserAgentInfo) that hold frozen placeholders
+/// rather than real data
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Default)]
+pub struct FrozenFields {
+    /// `os_version` is a placeholder, or for Windows, `os_release` cannot tell 10
+    /// from 11
+    pub os_version: bool,
+    /// The real OS version is at least this one, when the placeholder allows saying so
+    pub os_version_at_least: Option<Version>,
+    /// Only the major component of `browser_version` is real
+    pub browser_version: bool,
+    /// Only the major component of `engine_version` is real
+    pub engine_version: bool,
+    /// The device model is a placeholder (`K` on Android)
+    pub device_model: bool,
+}
+
+impl FrozenFields {
+    /// Whether any field is frozen
+    pub fn any(&self) -> bool {
+        self.os_version || self.browser_version || self.engine_version || self.device_model
+    }
+
+    /// `chrome_version` is the version of the `Chrome/` token. It stands in for the
+    /// browser version when none was found, as for Android Chrome, whose `Mobile
+    /// Safari` product hides the browser.
+    pub(crate) fn detect(
+        os: &OperatingSystem,
+        os_version: Option<&Version>,
+        browser_version: Option<&Version>,
+        chrome_version: Option<&Version>,
+        engine_version: Option<&Version>,
+        device: &Device,
+    ) -> FrozenFields {
+        let device_model = *os == OperatingSystem::Android && device.model_code.as_deref() == Some("K");
+        let (os_version, os_version_at_least) = match (os, os_version.map(|v| v.raw.as_str())) {
+            // Windows 11 still reports `Windows NT 10.0`
+            (OperatingSystem::Windows, Some("10.0")) => (true, Version::parse("10.0")),
+            (OperatingSystem::MacOS, Some("10.15.7" | "10.15")) => (true, Version::parse("10.15")),
+            (OperatingSystem::Android, Some("10")) if device_model => (true, None),
+            (OperatingSystem::ChromeOS, Some("14541.0.0")) => (true, None),
+            _ => (false, None),
+        };
+
+        FrozenFields {
+            os_version,
+            os_version_at_least,
+            browser_version: browser_version
+                .or(chrome_version)
+                .is_some_and(is_reduced_version),
+            engine_version: engine_version.is_some_and(is_reduced_version),
+            device_model,
+        }
+    }
+}
+
+/// Whether a version has the `major.0.0.0` shape of a reduced Chromium version
+pub(crate) fn is_reduced_version(version: &Version) -> bool {
+    version.minor == Some(0) && version.patch == Some(0) && version.build == Some(0)
+}
@@ -0,0 +1,216 @@
+//! The device description of Instagram's native app user agents.
+//!
+//! The Instagram apps describe the device in a comment after the app version:
+//!
+//! - Android: `Instagram 250.0.0.21.109 Android (31/12; 420dpi; 1080x2400; samsung;
+//!   SM-G991B; o1s; exynos2100; en_US; 394071253)`
+//! - iOS: `Instagram 309.1.1.28.108 (iPhone14,5; iOS 17_1_2; en_US; en; scale=3.00;
+//!   1170x2532; 541635890)`
+//!
+//! The entries are positional, in the order shown.
+
+use crate::device::android_device;
+use crate::token::{Haystack, Token};
+use crate::{
+    AppleModel, Confidence, Device, Evidence, OperatingSystem, Source, UserAgentInfo, Version,
+};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// The typed content of an Instagram app user agent
+///
+/// Fields the platform does not report are `None`: iOS sends no API level, density,
+/// manufacturer, codename or chipset.
+///
+/// # Example
+///
+/// ```
+/// use agent_parser_ro::UserAgentParser;
+///
+/// let info = UserAgentParser::parse("Instagram 250.0.0.21.109 Android (31/12; 420dpi; 1080x2400; samsung; SM-G991B; o1s; exynos2100; en_US; 394071253)");
+/// let instagram = info.instagram.unwrap();
+///
+/// assert_eq!(instagram.app_version.unwrap().major, 250);
+/// assert_eq!(instagram.api_level, Some(31));
+/// assert_eq!(instagram.dpi, Some(420));
+/// assert_eq!((instagram.width, instagram.height), (Some(1080), Some(2400)));
+/// assert_eq!(instagram.model.as_deref(), Some("SM-G991B"));
+/// assert_eq!(instagram.chipset.as_deref(), Some("exynos2100"));
+/// assert_eq!(info.os_version.unwrap().major, 12);
+/// ```
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct InstagramMetadata {
+    pub app_version: Option<Version>,
+    /// The Android API level, such as `31`
+    pub api_level: Option<u32>,
+    pub os_version: Option<Version>,
+    /// The Android screen density, such as `420` for `420dpi`
+    pub dpi: Option<u32>,
+    /// The screen's pixel ratio, from `scale=` on iOS or the density on Android
+    pub screen_scale: Option<f32>,
+    /// The screen width in pixels
+    pub width: Option<u32>,
+    /// The screen height in pixels
+    pub height: Option<u32>,
+    /// The manufacturer as reported, such as `samsung`
+    pub manufacturer: Option<String>,
+    /// The model code or Apple hardware identifier, such as `SM-G991B` or `iPhone14,5`
+    pub model: Option<String>,
+    /// The device codename, such as `o1s`
+    pub codename: Option<String>,
+    /// The chipset, such as `exynos2100` or `qcom`
+    pub chipset: Option<String>,
+    /// The app locale, such as `en_US`
+    pub locale: Option<String>,
+    /// The app's build number
+    pub build: Option<u64>,
+}
+
+/// The app version and the entries of the device comment that follows it
+struct DeviceComment<'a> {
+    app_version: Option<Version>,
+    entries: Vec<(&'a str, Range<usize>)>,
+}
+
+/// Finds the app version and the device comment that follows it
+fn device_comment<'a>(haystack: &Haystack<'a>) -> Option<DeviceComment<'a>> {
+    let tokens = haystack.tokens();
+    let app = tokens.iter().position(
+        |token| matches!(token, Token::Product(product) if product.name == "Instagram"),
+    )?;
+    let version = match tokens.get(app + 1) {
+        Some(Token::Product(product)) => Version::parse(product.name),
+        _ => None,
+    };
+    tokens[app + 1..]
+        .iter()
+        .take(3)
+        .find_map(|token| match token {
+            Token::Comment(comment) => Some(DeviceComment {
+                app_version: version.clone(),
+                entries: comment.entries().collect(),
+            }),
+            Token::Product(_) => None,
+        })
+}
+
+/// Splits `1080x2400` into width and height
+fn resolution(entry: &str) -> Option<(u32, u32)> {
+    let (width, height) = entry.split_once('x')?;
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+impl InstagramMetadata {
+    /// Parses the device comment of an Instagram app user agent
+    pub(crate) fn detect(haystack: &Haystack) -> Option<InstagramMetadata> {
+        let DeviceComment {
+            app_version,
+            entries,
+        } = device_comment(haystack)?;
+        let entry = |index: usize| {
+            entries
+                .get(index)
+                .map(|(entry, _)| *entry)
+                .filter(|entry| !entry.is_empty())
+        };
+        let text = |index: usize| entry(index).map(str::to_string);
+        let size = |index: usize| entry(index).and_then(resolution);
+
+        let metadata = if haystack.ua().contains(" Android (") {
+            let (api_level, os_version) = match entry(0).and_then(|entry| entry.split_once('/')) {
+                Some((api, os)) => (api.parse().ok(), Version::parse(os)),
+                None => (None, None),
+            };
+            let dpi: Option<u32> = entry(1)
+                .and_then(|entry| entry.strip_suffix("dpi"))
+                .and_then(|dpi| dpi.parse().ok());
+            InstagramMetadata {
+                app_version,
+                api_level,
+                os_version,
+                dpi,
+                // Android measures density against a 160 dpi baseline
+                screen_scale: dpi.map(|dpi| dpi as f32 / 160.0),
+                width: size(2).map(|(width, _)| width),
+                height: size(2).map(|(_, height)| height),
+                manufacturer: text(3),
+                model: text(4),
+                codename: text(5),
+                chipset: text(6),
+                locale: text(7),
+                build: entry(8).and_then(|build| build.parse().ok()),
+            }
+        } else {
+            let os_version = entry(1)
+                .and_then(|os| os.split_once(' '))
+                .and_then(|(_, version)| Version::parse(version))
+                .map(|mut version| {
+                    version.raw = version.raw.replace('_', ".");
+                    version
+                });
+            InstagramMetadata {
+                app_version,
+                os_version,
+                screen_scale: entry(4)
+                    .and_then(|scale| scale.strip_prefix("scale="))
+                    .and_then(|scale| scale.parse().ok()),
+                width: size(5).map(|(width, _)| width),
+                height: size(5).map(|(_, height)| height),
+                model: text(0),
+                locale: text(2),
+                build: entry(6).and_then(|build| build.parse().ok()),
+                ..InstagramMetadata::default()
+            }
+        };
+        Some(metadata)
+    }
+
+    /// Fills the OS, OS version and device of `info` when the rest of the user agent
+    /// left them out, as the app's API requests do
+    pub(crate) fn apply(&self, haystack: &Haystack, info: &mut UserAgentInfo) {
+        let ua = haystack.ua();
+        let Some(DeviceComment { entries, .. }) = device_comment(haystack) else {
+            return;
+        };
+        if info.os == OperatingSystem::Unknown
+            && let Some((os, span)) = entries.get(1)
+        {
+            info.os = match os.split(' ').next() {
+                Some("iOS") => OperatingSystem::IOS,
+                Some("iPadOS") => OperatingSystem::IPadOS,
+                _ => OperatingSystem::Unknown,
+            };
+            if info.os != OperatingSystem::Unknown {
+                let span = span.clone();
+                let evidence =
+                    Evidence::matched(ua, span, Source::InstagramMetadata, Confidence::High);
+                info.evidence.os = Some(evidence);
+            }
+        }
+        if info.os_version.is_none() {
+            info.os_version = self.os_version.clone();
+        }
+        // Reduced Android user agents replace the model code with `K`
+        let known_device = info
+            .device
+            .model_code
+            .as_deref()
+            .is_some_and(|code| code != "K");
+        if let Some(code) = &self.model
+            && !known_device
+        {
+            info.device = match AppleModel::lookup(code) {
+                Some(model) => Device {
+                    brand: Some("Apple".to_string()),
+                    model: Some(model.name.to_string()),
+                    model_code: Some(model.identifier),
+                },
+                None => {
+                    let mut device = android_device(code);
+                    device.brand = device.brand.or(self.manufacturer.clone());
+                    device
+                }
+            };
+        }
+    }
+}
@@ -4,6 +4,7 @@
 //! - Browser/Client (Chrome, Safari, Firefox, etc.)
 //! - Operating System (Windows, macOS, Android, etc.)
 //! - Device Type (Mobile, Tablet, Desktop, etc.)
+//! - Rendering Engine (Blink, WebKit, Gecko, etc.)
 //!
 //! # Examples
 //!
@@ -20,6 +21,53 @@
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Range;
+use token::{Haystack, Keywords};
+
+#[cfg(feature = "android-models")]
+mod android_models;
+mod apple_models;
+mod automation;
+mod bots;
+mod client_hints;
+mod device;
+mod error;
+mod evidence;
+mod explain;
+mod facebook;
+mod frozen;
+mod instagram;
+mod library;
+mod robots;
+mod rules;
+mod stack;
+mod token;
+#[cfg(feature = "uap")]
+mod uap;
+mod verify;
+mod version;
+
+pub use apple_models::{AppleDeviceClass, AppleModel};
+pub use automation::Automation;
+pub use bots::{Bot, BotCategory};
+pub use client_hints::{Brand, ClientHints, UserAgentData};
+pub use device::Device;
+pub use error::Error;
+pub use evidence::{Confidence, DetectionEvidence, Evidence, Source};
+pub use explain::{Explanation, Field, Outcome, TraceStep};
+pub use facebook::FacebookMetadata;
+pub use frozen::FrozenFields;
+pub use instagram::InstagramMetadata;
+pub use library::Library;
+pub use robots::{RobotsGroup, RobotsRule, RobotsTxt};
+pub use rules::{Rule, UserAgentParserBuilder};
+pub use stack::{App, ClientStack, Embedding, HostApp};
+pub use token::{tokenize, Comment, Product, Token};
+#[cfg(feature = "uap")]
+pub use uap::UapRules;
+pub use verify::{verify_bot, BotVerifier, Resolver, Verification};
+pub use version::Version;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
 pub enum Browser {
@@ -53,9 +101,57 @@ pub enum Browser {
     UCBrowser,
     SamsungBrowser,
     OculusBrowser,
+    /// A browser or app named by a custom rule
+    Other(String),
     Unknown,
 }
 
+impl Browser {
+    /// Tokens that carry this browser's version, in order of preference
+    fn version_tokens(&self) -> &'static [&'static str] {
+        match self {
+            Browser::Chrome => &["Chrome/", "CriOS/", "HeadlessChrome/"],
+            Browser::Safari => &["Version/"],
+            Browser::Firefox => &["Firefox/", "FxiOS/"],
+            Browser::Edge => &["Edg/", "Edge/", "EdgA/", "EdgiOS/"],
+            Browser::InternetExplorer => &["MSIE ", "rv:"],
+            Browser::Opera => &["OPR/", "Opera Mini/", "Version/", "Opera/", "Opera "],
+            Browser::Dolphin => &["Dolphin/", "DolphinBrowserCN/"],
+            Browser::Brave => &["Brave/", "Brave Chrome/"],
+            Browser::Puffin => &["Puffin/"],
+            Browser::Maxthon => &["Maxthon/", "Maxthon "],
+            Browser::Mercury => &["Mercury/"],
+            Browser::Silk => &["Silk/"],
+            Browser::Vivaldi => &["Vivaldi/"],
+            Browser::Yandex => &["YaBrowser/"],
+            Browser::DuckDuckGo => &["DuckDuckGo/", "Ddg/"],
+            Browser::Tor => &["TorBrowser/", "Tor/"],
+            Browser::Electron => &["Electron/"],
+            Browser::PhantomJS => &["PhantomJS/"],
+            Browser::WebView => &["Chrome/", "Version/"],
+            Browser::Facebook => &["FBAV/"],
+            Browser::Instagram => &["Instagram "],
+            Browser::Twitter => &["Twitter for iPhone/", "TwitterAndroid/", "Twitter/"],
+            Browser::Snapchat => &["Snapchat/"],
+            Browser::Googlebot => &["Googlebot/"],
+            Browser::Bingbot => &["bingbot/"],
+            Browser::Yahoo => &["Yahoo! Slurp/"],
+            Browser::Baidu => &["Baiduspider/"],
+            Browser::UCBrowser => &["UCBrowser/", "UCWEB/"],
+            Browser::SamsungBrowser => &["SamsungBrowser/"],
+            Browser::OculusBrowser => &["OculusBrowser/"],
+            Browser::Other(_) | Browser::Unknown => &[],
+        }
+    }
+
+    /// Finds this browser's version in the user agent
+    pub(crate) fn detect_version(&self, haystack: &Haystack) -> Option<Version> {
+        self.version_tokens()
+            .iter()
+            .find_map(|token| haystack.token_version(token))
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
 pub enum OperatingSystem {
     Windows,
@@ -85,9 +181,95 @@ pub enum OperatingSystem {
     HPUX,
     HarmonyOS,
     KaiOS,
+    /// An operating system named by a custom rule
+    Other(String),
     Unknown,
 }
 
+impl OperatingSystem {
+    /// Tokens that carry this OS's version, in order of preference
+    fn version_tokens(&self) -> &'static [&'static str] {
+        match self {
+            OperatingSystem::Windows => &["Windows NT "],
+            OperatingSystem::WindowsPhone => &["Windows Phone OS ", "Windows Phone "],
+            OperatingSystem::MacOS => &["Mac OS X "],
+            OperatingSystem::IOS => &["iPhone OS ", "CPU OS "],
+            OperatingSystem::IPadOS => &["CPU OS ", "iPhone OS "],
+            OperatingSystem::Android => &["Android ", "Android/"],
+            OperatingSystem::Ubuntu => &["Ubuntu/"],
+            OperatingSystem::Fedora => &["Fedora/"],
+            OperatingSystem::Debian => &["Debian/"],
+            OperatingSystem::ChromeOS => &["CrKey/"],
+            OperatingSystem::Symbian => &["SymbianOS/", "Symbian/"],
+            OperatingSystem::WebOS => &["webOS/", "webOS "],
+            OperatingSystem::Bada => &["Bada/"],
+            OperatingSystem::Tizen => &["Tizen ", "Tizen/"],
+            OperatingSystem::HarmonyOS => &["HarmonyOS ", "OpenHarmony "],
+            OperatingSystem::KaiOS => &["KaiOS/"],
+            _ => &[],
+        }
+    }
+
+    /// Decodes this OS's version from the user agent using the platform's own notation
+    pub(crate) fn detect_version(&self, haystack: &Haystack) -> Option<Version> {
+        lazy_static! {
+            static ref OS_VERSION_REGEX: [(OperatingSystem, Regex); 3] = [
+                (
+                    OperatingSystem::ChromeOS,
+                    Regex::new(r"(?i)\bcros [a-z0-9_]+ (\d[\d.]*)").unwrap(),
+                ),
+                (
+                    OperatingSystem::PlayStation,
+                    Regex::new(r"(?i)playstation (?:\d|vita|portable)[ );/]*(\d+\.\d+)").unwrap(),
+                ),
+                (
+                    OperatingSystem::BlackBerry,
+                    Regex::new(r"(?i)blackberry ?\d*/(\d[\d.]*)").unwrap(),
+                ),
+            ];
+        }
+
+        let mut version = OS_VERSION_REGEX
+            .iter()
+            .filter(|(os, _)| os == self)
+            .find_map(|(_, reg)| reg.captures(haystack.ua()))
+            .and_then(|caps| Version::parse(caps.get(1).unwrap().as_str()))
+            .or_else(|| {
+                self.version_tokens()
+                    .iter()
+                    .find_map(|token| haystack.token_version(token))
+            })?;
+
+        // iOS and macOS separate components with underscores
+        version.raw = version.raw.replace('_', ".");
+        Some(version)
+    }
+
+    /// Names the release an OS version belongs to, where the two differ
+    pub(crate) fn release(&self, version: Option<&Version>) -> Option<String> {
+        match self {
+            OperatingSystem::Windows => version.and_then(windows_release).map(str::to_string),
+            _ => None,
+        }
+    }
+}
+
+/// Names the Windows release that shipped a Windows NT kernel version
+///
+/// Windows 11 still reports NT 10.0, so only client hints tell it apart from 10.
+fn windows_release(nt: &Version) -> Option<&'static str> {
+    match (nt.major, nt.minor.unwrap_or(0)) {
+        (5, 0) => Some("2000"),
+        (5, 1) | (5, 2) => Some("XP"),
+        (6, 0) => Some("Vista"),
+        (6, 1) => Some("7"),
+        (6, 2) => Some("8"),
+        (6, 3) => Some("8.1"),
+        (10, 0) => Some("10"),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
 pub enum DeviceType {
     Mobile,
@@ -99,17 +281,174 @@ pub enum DeviceType {
     VRHeadset,
     CarSystem,
     Bot,
+    /// A device family from a rule file that names no known form factor
+    Other(String),
+    Unknown,
+}
+
+/// What kind of software sent the request
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ClientKind {
+    /// A web browser, including in-app browsers and web views
+    Browser,
+    /// An HTTP client library or command-line tool such as curl or `python-requests`
+    Library,
+    /// A crawler or other automated client
+    Bot,
+    Unknown,
+}
+
+impl ClientKind {
+    /// Classifies a parse result, preferring the most specific detection
+    fn classify(info: &UserAgentInfo) -> ClientKind {
+        if info.bot.is_some() {
+            ClientKind::Bot
+        } else if info.library.is_some() {
+            ClientKind::Library
+        } else if info.browser != Browser::Unknown {
+            ClientKind::Browser
+        } else {
+            ClientKind::Unknown
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+pub enum Engine {
+    Blink,
+    WebKit,
+    Gecko,
+    Trident,
+    EdgeHTML,
+    Presto,
+    Unknown,
+}
+
+impl Engine {
+    /// Detects the rendering engine from its own tokens rather than the browser brand
+    fn detect(haystack: &Haystack, os: &OperatingSystem) -> (Engine, Option<Version>) {
+        let token = |token: &str| haystack.token_version(token);
+
+        if let Some(version) = token("Presto/") {
+            return (Engine::Presto, Some(version));
+        }
+        // Legacy Edge reports its EdgeHTML version in `Edge/`, Chromium Edge uses `Edg/`
+        if let Some(version) = token("Edge/") {
+            return (Engine::EdgeHTML, Some(version));
+        }
+        if let Some(version) = token("Trident/") {
+            return (Engine::Trident, Some(version));
+        }
+        if token("MSIE ").is_some() {
+            return (Engine::Trident, None);
+        }
+        if let Some(webkit) = token("AppleWebKit/") {
+            // Every browser on iOS has to use the system WebKit
+            if matches!(os, OperatingSystem::IOS | OperatingSystem::IPadOS) {
+                return (Engine::WebKit, Some(webkit));
+            }
+            // Blink forked from WebKit in Chromium 28 and follows Chromium's version
+            let chromium = token("Chrome/").or_else(|| token("HeadlessChrome/"));
+            return match chromium {
+                Some(version) if version.major >= 28 => (Engine::Blink, Some(version)),
+                _ => (Engine::WebKit, Some(webkit)),
+            };
+        }
+        if let Some(gecko) = token("Gecko/") {
+            // `Gecko/` is usually a frozen build date, the real version is in `rv:`
+            return (Engine::Gecko, token("rv:").or(Some(gecko)));
+        }
+        (Engine::Unknown, None)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+pub enum Architecture {
+    X86,
+    X86_64,
+    Arm,
+    Arm64,
+    PPC,
     Unknown,
 }
 
+impl Architecture {
+    /// Detects the CPU architecture and whether it is 64-bit
+    ///
+    /// `WOW64` (a 32-bit browser on 64-bit Windows) is reported as `X86_64`, since it
+    /// describes the machine rather than the browser build.
+    fn detect(ua: &str) -> (Architecture, Option<bool>) {
+        lazy_static! {
+            static ref ARCH_REGEX: Regex = Regex::new(
+                r"(?i)\b(x86_64|x86-64|x64|win64|wow64|amd64|aarch64|arm64|armv8l|armv8|armv7l?|armv6l?|arm|i[3-6]86|x86|ppc64|ppc|powerpc)\b"
+            ).unwrap();
+        }
+
+        let Some(caps) = ARCH_REGEX.captures(ua) else {
+            return (Architecture::Unknown, None);
+        };
+        let arch = caps.get(1).unwrap().as_str().to_lowercase();
+        match arch.as_str() {
+            "x86_64" | "x86-64" | "x64" | "win64" | "wow64" | "amd64" => {
+                (Architecture::X86_64, Some(true))
+            }
+            "aarch64" | "arm64" | "armv8" => (Architecture::Arm64, Some(true)),
+            "armv8l" | "armv7" | "armv7l" | "armv6" | "armv6l" | "arm" => {
+                (Architecture::Arm, Some(false))
+            }
+            "ppc64" => (Architecture::PPC, Some(true)),
+            "ppc" | "powerpc" => (Architecture::PPC, Some(false)),
+            _ => (Architecture::X86, Some(false)),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserAgentInfo {
     pub os: OperatingSystem,
+    /// The OS version in the platform's own numbering: the NT kernel version for
+    /// Windows, as in `Windows NT 6.1`
+    pub os_version: Option<Version>,
+    /// The release name, where it differs from `os_version`, such as `7`, `XP` or
+    /// `11` for Windows
+    pub os_release: Option<String>,
     pub browser: Browser,
+    pub browser_version: Option<Version>,
     pub device_type: DeviceType,
+    pub device: Device,
+    pub engine: Engine,
+    pub engine_version: Option<Version>,
+    pub architecture: Architecture,
+    pub is_64bit: Option<bool>,
+    pub frozen: FrozenFields,
+    /// Which tokens and rules decided `os`, `browser` and `device_type`
+    pub evidence: DetectionEvidence,
+    /// The crawler or other automated client, when the user agent is a bot
+    pub bot: Option<Bot>,
+    pub client_kind: ClientKind,
+    /// The HTTP client library or command-line tool, when the user agent names one
+    pub library: Option<Library>,
+    /// Headless, automation and synthetic testing signals, in order of appearance
+    pub automation: Vec<Automation>,
+    /// The host app, embedding and browser layers of in-app browsers and web views
+    pub client_stack: ClientStack,
+    /// The `[FBAN/...;FBAV/...]` block of Facebook's in-app browser
+    pub facebook: Option<FacebookMetadata>,
+    /// The device description of Instagram's native app user agents
+    pub instagram: Option<InstagramMetadata>,
 }
 
-pub struct UserAgentParser;
+/// Parses user agent strings
+///
+/// The associated functions such as [`UserAgentParser::parse`] use the built-in
+/// rules only. Use [`UserAgentParser::builder`] to build a parser instance with
+/// custom rules on top.
+#[derive(Debug, Default)]
+pub struct UserAgentParser {
+    rules: Vec<rules::CompiledRule>,
+    #[cfg(feature = "uap")]
+    uap: Option<uap::CompiledUap>,
+}
 
 impl UserAgentParser {
     /// Parses a user agent string and returns detected information
@@ -130,44 +469,94 @@ impl UserAgentParser {
     /// let info = UserAgentParser::parse("Mozilla/5.0 (iPhone; CPU iPhone OS 14_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/14.0 Mobile/15E148 Safari/604.1");
     /// ```
     pub fn parse(ua: &str) -> UserAgentInfo {
-        lazy_static! {
-            // Updated OS regex to better handle Android and other mobile OS patterns
-            static ref OS_REGEX: [Regex; 2] =[
-                Regex::new(
-                r"(?i)(windows phone|mac os x|iphone os|ipad; cpu os|android|ubuntu|fedora|debian|cros|crkey|chrome os|blackberry|symbian|webos|bada|tizen|nintendo|playstation|xbox|wii|freebsd|openbsd|solaris|aix|hp-ux|harmonyos|kaios)"
-            ).unwrap(),
-                Regex::new(
-                 r"(?i)(windows|linux)"
-            ).unwrap()
-            ];
+        UserAgentParser::parse_traced(ua, None)
+    }
 
-            static ref BROWSER_REGEX: [Regex; 2] = [
-                Regex::new(
-                 r"(?i)(ucbrowser|samsungbrowser|oculusbrowser|ucweb|crios|headlesschrome|mobile safari|fxios|edge|edg|edga|edgios|msie|trident|opera|opr|dolphin|brave|puffin|maxthon|mercury|nokiabrowser|silk|vivaldi|yabrowser|duckduckgo|tor|electron|phantomjs|wv|fban|fbav|instagram|twitter|snapchat|googlebot|bingbot|yahoo! slurp|baiduspider)"
-            ).unwrap(),
-                Regex::new(
-                 r"(?i)(chrome|safari|firefox)"
-            ).unwrap()];
-
-            static ref DEVICE_REGEX: [Regex; 2] =[
-                 Regex::new(
-                r"(?i)(kfmawi|ipod|windows phone|blackberry|symbian|ipad|tablet|kindle|playbook|nexus|sm-t|sm-x|sm-s|gt-p|playstation|ps4|ps5|xbox|nintendo|wii|smart-tv|tv|appletv|roku|chromecast|crkey|fire tv|watch|apple watch|vive|oculus|tesla|android auto|carplay|googlebot|bingbot|slurp|baiduspider|facebookexternalhit|twitterbot|monitoring|scraper|yandexbot)"
-            ).unwrap(),
-                Regex::new(
-                r"(?i)(android|iphone|x11|x86_64)"
-            ).unwrap()
-            ];
-        }
+    /// Parses a user agent string like [`UserAgentParser::parse`] and also returns
+    /// every rule tier and fallback tried for the OS, browser and device type, in order
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use agent_parser_ro::{Field, Outcome, UserAgentParser};
+    ///
+    /// let explanation = UserAgentParser::explain("Mozilla/5.0 (Linux; U; Android 4.0.3; en-us; KFTT Build/IML74K) AppleWebKit/537.36 (KHTML, like Gecko) Silk/3.68 like Chrome/39.0.2171.93 Safari/537.36");
+    ///
+    /// let browser = explanation.steps.iter().find(|step| step.field == Field::Browser).unwrap();
+    /// assert_eq!(browser.stage, "BROWSER_REGEX[0]");
+    /// assert_eq!(browser.outcome, Outcome::Detected("Silk".to_string()));
+    /// println!("{}", explanation);
+    /// ```
+    pub fn explain(ua: &str) -> Explanation {
+        let mut steps = Vec::new();
+        let info = UserAgentParser::parse_traced(ua, Some(&mut steps));
+        Explanation { info, steps }
+    }
+
+    fn parse_traced(ua: &str, mut trace: Option<&mut Vec<TraceStep>>) -> UserAgentInfo {
+        use token::Scope::{Comment, Product, Text};
+        // Each keyword is limited to the product names, comments or free text of the
+        // user agent, so that short keywords do not match inside longer words
+        const OS_REGEX: [Keywords; 2] = [
+            Keywords(&[
+                ("windows phone", Comment), ("mac os x", Comment), ("iphone os", Comment),
+                ("ipad; cpu os", Comment), ("android", Text), ("ubuntu", Text), ("fedora", Text),
+                ("debian", Text), ("cros", Comment), ("crkey", Product), ("chrome os", Text),
+                ("blackberry", Text), ("symbian", Text), ("webos", Text), ("bada", Text),
+                ("tizen", Text), ("nintendo", Text), ("playstation", Text), ("xbox", Text),
+                ("wii", Comment), ("freebsd", Comment), ("openbsd", Comment), ("solaris", Comment),
+                ("aix", Comment), ("hp-ux", Comment), ("harmonyos", Comment), ("kaios", Text),
+            ]),
+            Keywords(&[("windows", Text), ("linux", Text)]),
+        ];
+
+        const BROWSER_REGEX: [Keywords; 2] = [
+            Keywords(&[
+                ("ucbrowser", Product), ("samsungbrowser", Product), ("oculusbrowser", Product),
+                ("ucweb", Product), ("crios", Product), ("headlesschrome", Product),
+                ("mobile safari", Product), ("fxios", Product), ("edge", Product), ("edg", Product),
+                ("edga", Product), ("edgios", Product), ("msie", Comment), ("trident", Comment),
+                ("opera", Text), ("opr", Product), ("dolphin", Product), ("dolphinbrowsercn", Product),
+                ("brave", Product), ("puffin", Product), ("maxthon", Product), ("mercury", Product),
+                ("nokiabrowser", Product), ("silk", Product), ("vivaldi", Product),
+                ("yabrowser", Product), ("duckduckgo", Product), ("tor", Product),
+                ("electron", Product), ("phantomjs", Product), ("wv", Comment), ("fban", Text),
+                ("fbav", Text), ("instagram", Product), ("twitter", Product), ("snapchat", Product),
+                ("googlebot", Text), ("bingbot", Text), ("yahoo! slurp", Text), ("baiduspider", Text),
+            ]),
+            Keywords(&[("chrome", Product), ("safari", Product), ("firefox", Product)]),
+        ];
+
+        const DEVICE_REGEX: [Keywords; 2] = [
+            Keywords(&[
+                ("kfmawi", Comment), ("ipod", Comment), ("windows phone", Comment),
+                ("blackberry", Text), ("symbian", Text), ("ipad", Comment), ("tablet", Comment),
+                ("kindle", Text), ("playbook", Comment), ("nexus", Comment), ("sm-t", Comment),
+                ("sm-x", Comment), ("sm-s", Comment), ("gt-p", Comment), ("playstation", Text),
+                ("ps4", Comment), ("ps5", Comment), ("xbox", Text), ("nintendo", Text),
+                ("wii", Comment), ("smart-tv", Text), ("tv", Comment), ("dtv", Comment),
+                ("appletv", Text), ("roku", Text), ("chromecast", Text), ("crkey", Product),
+                ("fire tv", Text), ("watch", Comment), ("apple watch", Text), ("vive", Comment),
+                ("oculus", Text), ("tesla", Text), ("android auto", Text), ("carplay", Text),
+                ("googlebot", Text), ("bingbot", Text), ("slurp", Text), ("baiduspider", Text),
+                ("facebookexternalhit", Text), ("twitterbot", Text), ("monitoring", Text),
+                ("scraper", Text), ("yandexbot", Text),
+            ]),
+            Keywords(&[("android", Text), ("iphone", Comment), ("x11", Comment), ("x86_64", Comment)]),
+        ];
 
         // Default values
         let mut os = OperatingSystem::Unknown;
         let mut browser = Browser::Unknown;
         let mut device_type = DeviceType::Unknown;
+        let mut evidence = DetectionEvidence::default();
+        let haystack = Haystack::new(ua);
         // Detect OS - now handles Android better
-        for reg in OS_REGEX.iter() {
-            if let Some(caps) = reg.captures(ua) {
-                let matched_os = caps.get(1).unwrap().as_str().to_lowercase();
-                os = match matched_os.as_str() {
+        for (tier, keywords) in OS_REGEX.iter().enumerate() {
+            let found = keywords.find(&haystack);
+            if let Some((matched_os, span)) = found.clone() {
+                evidence.os = Some(Evidence::keyword(ua, matched_os, span, tier));
+                os = match matched_os {
                     "windows" => OperatingSystem::Windows,
                     "windows phone" => OperatingSystem::WindowsPhone,
                     "mac os x" => OperatingSystem::MacOS,
@@ -198,16 +587,19 @@ impl UserAgentParser {
                     _ => OperatingSystem::Unknown,
                 };
             }
+            let detected = (os != OperatingSystem::Unknown).then_some(&os as &dyn fmt::Debug);
+            let (stage, last) = (("OS_REGEX", tier), tier + 1 == OS_REGEX.len());
+            trace_tier(&mut trace, Field::Os, stage, last, ua, found, detected);
             if os != OperatingSystem::Unknown {
                 break;
             }
         }
 
         // Detect Browser
-        for reg in BROWSER_REGEX.iter() {
-            if let Some(caps) = reg.captures(ua) {
-                let matched_browser = caps.get(1).unwrap().as_str().to_lowercase();
-                browser = match matched_browser.as_str() {
+        for (tier, keywords) in BROWSER_REGEX.iter().enumerate() {
+            let found = keywords.find(&haystack);
+            if let Some((matched_browser, span)) = found.clone() {
+                browser = match matched_browser {
                     "chrome" | "headlesschrome" | "crios" => Browser::Chrome,
                     "safari" | "mobile safari" => Browser::Safari,
                     "firefox" | "fxios" => Browser::Firefox,
@@ -217,7 +609,7 @@ impl UserAgentParser {
                     "ucbrowser" | "ucweb" => Browser::UCBrowser,
                     "samsungbrowser" => Browser::SamsungBrowser,
                     "oculusbrowser" => Browser::OculusBrowser,
-                    "dolphin" => Browser::Dolphin,
+                    "dolphin" | "dolphinbrowsercn" => Browser::Dolphin,
                     "brave" => Browser::Brave,
                     "puffin" => Browser::Puffin,
                     "maxthon" => Browser::Maxthon,
@@ -241,15 +633,21 @@ impl UserAgentParser {
                     _ => Browser::Unknown,
                 };
                 if browser != Browser::Unknown {
-                    break;
+                    evidence.browser = Some(Evidence::keyword(ua, matched_browser, span, tier));
                 }
             }
+            let detected = (browser != Browser::Unknown).then_some(&browser as &dyn fmt::Debug);
+            let (stage, last) = (("BROWSER_REGEX", tier), tier + 1 == BROWSER_REGEX.len());
+            trace_tier(&mut trace, Field::Browser, stage, last, ua, found, detected);
+            if browser != Browser::Unknown {
+                break;
+            }
         }
 
-        for reg in DEVICE_REGEX.iter() {
-            if let Some(caps) = reg.captures(ua) {
-                let device = caps.get(1).unwrap().as_str().to_lowercase();
-                device_type = match device.as_str() {
+        for (tier, keywords) in DEVICE_REGEX.iter().enumerate() {
+            let found = keywords.find(&haystack);
+            if let Some((device, span)) = found.clone() {
+                device_type = match device {
                     "x11" | "x86_64" => DeviceType::Desktop,
                     "iphone" | "ipod" | "android" | "windows phone" | "blackberry"|"sm-s" | "symbian" => {
                         DeviceType::Mobile
@@ -258,7 +656,7 @@ impl UserAgentParser {
                         DeviceType::Tablet
                     }
                     "playstation" | "ps4" | "ps5" | "xbox" | "nintendo" | "wii" => DeviceType::Game,
-                    "smart-tv" | "tv" | "appletv" | "roku" | "chromecast"| "crkey" | "fire tv" => {
+                    "smart-tv" | "tv" | "dtv" | "appletv" | "roku" | "chromecast"| "crkey" | "fire tv" => {
                         DeviceType::TV
                     }
                     "watch" | "apple watch" => DeviceType::Smartwatch,
@@ -276,20 +674,268 @@ impl UserAgentParser {
                     _ => DeviceType::Unknown,
                 };
                 if device_type != DeviceType::Unknown {
-                    break;
+                    evidence.device_type = Some(Evidence::keyword(ua, device, span, tier));
                 }
             }
+            let detected =
+                (device_type != DeviceType::Unknown).then_some(&device_type as &dyn fmt::Debug);
+            // The bot list and the desktop fallback follow the device tiers
+            let stage = ("DEVICE_REGEX", tier);
+            trace_tier(&mut trace, Field::DeviceType, stage, false, ua, found, detected);
+            if device_type != DeviceType::Unknown {
+                break;
+            }
         }
-        if device_type == DeviceType::Unknown {
-            if ua.contains("Windows") || ua.contains("Macintosh") || ua.contains("Linux") {
+        let bot = Bot::detect(&haystack).map(|(bot, bot_evidence)| {
+            device_type = DeviceType::Bot;
+            evidence.device_type = Some(bot_evidence);
+            bot
+        });
+        if let Some(trace) = trace.as_deref_mut() {
+            let matched = bot.as_ref().and(evidence.device_type.clone());
+            let detected = bot.as_ref().map(|_| format!("{:?}", DeviceType::Bot));
+            trace.push(TraceStep::new(Field::DeviceType, "KNOWN_BOTS", matched, detected));
+        }
+        if device_type != DeviceType::Unknown {
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.push(TraceStep {
+                    field: Field::DeviceType,
+                    stage: "desktop fallback".to_string(),
+                    matched: None,
+                    outcome: Outcome::Skipped,
+                    last: true,
+                });
+            }
+        } else {
+            let fallback = ["Windows", "Macintosh", "Linux"].iter().find_map(|name| {
+                ua.find(name).map(|start| {
+                    Evidence::matched(
+                        ua,
+                        start..start + name.len(),
+                        Source::DesktopFallback,
+                        Confidence::Low,
+                    )
+                })
+            });
+            if fallback.is_some() {
                 device_type = DeviceType::Desktop;
             }
-        };
-        UserAgentInfo {
+            if let Some(trace) = trace.as_deref_mut() {
+                let detected = fallback.is_some().then(|| format!("{:?}", device_type));
+                let step =
+                    TraceStep::new(Field::DeviceType, "desktop fallback", fallback.clone(), detected);
+                trace.push(step.last(true));
+            }
+            evidence.device_type = fallback;
+        }
+
+        let os_version = os.detect_version(&haystack);
+        let os_release = os.release(os_version.as_ref());
+        let browser_version = browser.detect_version(&haystack);
+        let (engine, engine_version) = Engine::detect(&haystack, &os);
+        let (architecture, is_64bit) = Architecture::detect(ua);
+        let device = Device::detect(&haystack);
+        // Apple hardware identifiers pin down the exact device class
+        if let Some(model) = device.model_code.as_deref().and_then(AppleModel::lookup) {
+            device_type = model.class.device_type();
+            evidence.device_type = Some(match ua.find(&model.identifier) {
+                Some(start) => Evidence::matched(
+                    ua,
+                    start..start + model.identifier.len(),
+                    Source::AppleModel,
+                    Confidence::High,
+                ),
+                None => Evidence {
+                    token: model.identifier.clone(),
+                    span: None,
+                    rule: Source::AppleModel,
+                    confidence: Confidence::High,
+                },
+            });
+            if let Some(trace) = trace.as_deref_mut() {
+                let detected = Some(format!("{:?}", device_type));
+                let matched = evidence.device_type.clone();
+                trace.push(TraceStep::new(Field::DeviceType, "Apple model", matched, detected));
+            }
+        }
+        let client_stack = ClientStack::detect(
+            &haystack,
+            bot.is_some(),
+            &os,
+            &browser,
+            browser_version.as_ref(),
+            &engine,
+            engine_version.as_ref(),
+        );
+
+        let mut info = UserAgentInfo {
             os,
+            os_version,
+            os_release,
             browser,
+            browser_version,
             device_type,
+            device,
+            engine,
+            engine_version,
+            architecture,
+            is_64bit,
+            frozen: FrozenFields::default(),
+            evidence,
+            bot,
+            client_kind: ClientKind::Unknown,
+            library: Library::detect(&haystack),
+            automation: Automation::detect(&haystack),
+            client_stack,
+            facebook: FacebookMetadata::detect(&haystack),
+            instagram: InstagramMetadata::detect(&haystack),
+        };
+        // The apps' own metadata is more precise than the rest of the user agent
+        if let Some(facebook) = info.facebook.clone() {
+            facebook.apply(&haystack, &mut info);
+            trace_metadata(&mut trace, &info, Source::FacebookMetadata, "Facebook metadata");
         }
+        if let Some(instagram) = info.instagram.clone() {
+            instagram.apply(&haystack, &mut info);
+            trace_metadata(&mut trace, &info, Source::InstagramMetadata, "Instagram metadata");
+        }
+        info.frozen = FrozenFields::detect(
+            &info.os,
+            info.os_version.as_ref(),
+            info.browser_version.as_ref(),
+            haystack.token_version("Chrome/").as_ref(),
+            info.engine_version.as_ref(),
+            &info.device,
+        );
+        info.client_kind = ClientKind::classify(&info);
+        info
+    }
+
+    /// Parses a user agent string and refines the result with User-Agent Client Hints
+    ///
+    /// Hints take precedence over the user agent string, which Chromium browsers now
+    /// freeze. GREASE brands are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use agent_parser_ro::{Browser, ClientHints, UserAgentParser};
+    ///
+    /// let hints = ClientHints {
+    ///     ua: Some(r#""Brave";v="120", "Chromium";v="120", "Not_A Brand";v="24""#.to_string()),
+    ///     platform: Some(r#""Windows""#.to_string()),
+    ///     platform_version: Some(r#""15.0.0""#.to_string()),
+    ///     ..ClientHints::default()
+    /// };
+    /// let info = UserAgentParser::parse_with_hints("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36", &hints);
+    ///
+    /// assert_eq!(info.browser, Browser::Brave);
+    /// assert_eq!(info.os_release.as_deref(), Some("11"));
+    /// ```
+    pub fn parse_with_hints(ua: &str, hints: &ClientHints) -> UserAgentInfo {
+        UserAgentParser::default().detect_with_hints(ua, hints)
+    }
+
+    /// Parses a user agent string and refines the result with the output of
+    /// `navigator.userAgentData.getHighEntropyValues()`
+    ///
+    /// Produces the same result as [`UserAgentParser::parse_with_hints`] given the
+    /// equivalent `Sec-CH-UA-*` headers.
+    pub fn parse_with_user_agent_data(ua: &str, data: &UserAgentData) -> UserAgentInfo {
+        UserAgentParser::default().detect_with_user_agent_data(ua, data)
+    }
+
+    /// Starts building a parser with custom rules
+    pub fn builder() -> UserAgentParserBuilder {
+        UserAgentParserBuilder::default()
+    }
+
+    /// Parses a user agent string with the built-in rules and this parser's custom rules
+    pub fn detect(&self, ua: &str) -> UserAgentInfo {
+        self.refine(ua, UserAgentParser::parse(ua), None)
+    }
+
+    /// Like [`UserAgentParser::parse_with_hints`], with this parser's custom rules
+    pub fn detect_with_hints(&self, ua: &str, hints: &ClientHints) -> UserAgentInfo {
+        self.refine(ua, UserAgentParser::parse(ua), Some(hints.values()))
+    }
+
+    /// Like [`UserAgentParser::parse_with_user_agent_data`], with this parser's custom rules
+    pub fn detect_with_user_agent_data(&self, ua: &str, data: &UserAgentData) -> UserAgentInfo {
+        self.refine(ua, UserAgentParser::parse(ua), Some(data.values()))
+    }
+
+    /// Layers rule files, client hints and custom rules over the built-in detection
+    ///
+    /// The client stack and kind summarize the other fields, so they are derived
+    /// again from the refined ones.
+    fn refine(
+        &self,
+        ua: &str,
+        mut info: UserAgentInfo,
+        hints: Option<client_hints::HintValues>,
+    ) -> UserAgentInfo {
+        #[cfg(feature = "uap")]
+        if let Some(uap) = &self.uap {
+            uap.apply(ua, &mut info);
+        }
+        if let Some(hints) = hints {
+            hints.apply(&mut info);
+        }
+        rules::apply(&self.rules, ua, &mut info);
+        info.client_stack = ClientStack::detect(
+            &Haystack::new(ua),
+            info.bot.is_some(),
+            &info.os,
+            &info.browser,
+            info.browser_version.as_ref(),
+            &info.engine,
+            info.engine_version.as_ref(),
+        );
+        info.client_kind = ClientKind::classify(&info);
+        info
+    }
+}
+
+/// Records one keyword tier in the trace, if tracing
+fn trace_tier(
+    trace: &mut Option<&mut Vec<TraceStep>>,
+    field: Field,
+    (table, tier): (&str, usize),
+    last: bool,
+    ua: &str,
+    found: Option<(&str, Range<usize>)>,
+    detected: Option<&dyn fmt::Debug>,
+) {
+    if let Some(trace) = trace.as_deref_mut() {
+        let matched = found.map(|(keyword, span)| Evidence::keyword(ua, keyword, span, tier));
+        let detected = detected.map(|value| format!("{:?}", value));
+        let step = TraceStep::new(field, format!("{}[{}]", table, tier), matched, detected);
+        trace.push(step.last(last));
     }
 }
 
+/// Records the fields an app's metadata decided in the trace, if tracing
+fn trace_metadata(
+    trace: &mut Option<&mut Vec<TraceStep>>,
+    info: &UserAgentInfo,
+    source: Source,
+    stage: &str,
+) {
+    let Some(trace) = trace.as_deref_mut() else {
+        return;
+    };
+    let evidence = &info.evidence;
+    let fields = [
+        (Field::Os, &evidence.os, format!("{:?}", info.os)),
+        (Field::Browser, &evidence.browser, format!("{:?}", info.browser)),
+        (Field::DeviceType, &evidence.device_type, format!("{:?}", info.device_type)),
+    ];
+    for (field, matched, detected) in fields {
+        if let Some(matched) = matched
+            && matched.rule == source
+        {
+            trace.push(TraceStep::new(field, stage, Some(matched.clone()), Some(detected)));
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! HTTP client libraries and command-line tools.
+//!
+//! These clients send a bare `name/version` product token, such as `curl/8.4.0` or
+//! `python-requests/2.31.0`, and nothing a browser would send.
+
+use crate::Version;
+use crate::token::{Haystack, Token};
+use serde::{Deserialize, Serialize};
+
+/// An HTTP client library or command-line tool
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+pub struct Library {
+    /// The library's name, such as `python-requests` or `OkHttp`
+    pub name: String,
+    pub version: Option<Version>,
+}
+
+/// `(product token, name)`, most specific first
+///
+/// Libraries built on a runtime's own client may mention the runtime too, as in
+/// `Python/3.11 aiohttp/3.9.1`, so runtimes come last.
+#[rustfmt::skip]
+const KNOWN_LIBRARIES: &[(&str, &str)] = &[
+    ("curl", "curl"),
+    ("wget", "Wget"),
+    ("httpie", "HTTPie"),
+    ("postmanruntime", "Postman"),
+    ("insomnia", "Insomnia"),
+    ("python-requests", "python-requests"),
+    ("python-httpx", "HTTPX"),
+    ("aiohttp", "aiohttp"),
+    ("python-urllib3", "urllib3"),
+    ("okhttp", "OkHttp"),
+    ("apache-httpclient", "Apache HttpClient"),
+    ("apache-httpasyncclient", "Apache HttpAsyncClient"),
+    ("reactor-netty", "Reactor Netty"),
+    ("node-fetch", "node-fetch"),
+    ("axios", "axios"),
+    ("got", "Got"),
+    ("undici", "undici"),
+    ("reqwest", "reqwest"),
+    ("hyper", "hyper"),
+    ("guzzlehttp", "Guzzle"),
+    ("libwww-perl", "libwww-perl"),
+    ("faraday", "Faraday"),
+    ("dart", "Dart"),
+    ("restsharp", "RestSharp"),
+    ("python-urllib", "urllib"),
+    ("go-http-client", "Go-http-client"),
+    ("java-http-client", "Java HttpClient"),
+    ("java", "Java"),
+    ("node", "Node.js"),
+];
+
+impl Library {
+    /// Finds a known library among the product tokens of the user agent
+    pub(crate) fn detect(haystack: &Haystack) -> Option<Library> {
+        KNOWN_LIBRARIES.iter().find_map(|&(token, name)| {
+            haystack
+                .tokens()
+                .iter()
+                .find_map(|candidate| match candidate {
+                    Token::Product(product) if product.name.eq_ignore_ascii_case(token) => {
+                        Some(Library {
+                            name: name.to_string(),
+                            version: product.version.and_then(Version::parse),
+                        })
+                    }
+                    _ => None,
+                })
+        })
+    }
+}
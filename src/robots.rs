@@ -0,0 +1,218 @@
+//! robots.txt group selection and path matching as specified by RFC 9309.
+//!
+//! The crawler's product token comes from the bot detection of this crate, so
+//! the group a user agent is held to agrees with who the parser says it is.
+
+use crate::UserAgentInfo;
+use serde::{Deserialize, Serialize};
+
+/// A parsed robots.txt file
+///
+/// Parsing is lenient as the RFC asks: unknown lines, rules outside a group and
+/// malformed lines are ignored.
+///
+/// # Example
+///
+/// ```
+/// use agent_parser_ro::{RobotsTxt, UserAgentParser};
+///
+/// let robots = RobotsTxt::parse(
+///     "User-agent: Googlebot\nDisallow: /private/\nAllow: /private/press/\n\nUser-agent: *\nDisallow: /",
+/// );
+/// let googlebot = UserAgentParser::parse("Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)");
+/// assert!(robots.is_allowed(&googlebot, "/private/press/2024.html"));
+/// assert!(!robots.is_allowed(&googlebot, "/private/plans.html"));
+///
+/// let bingbot = UserAgentParser::parse("Mozilla/5.0 (compatible; bingbot/2.0; +http://www.bing.com/bingbot.htm)");
+/// assert_eq!(robots.group_for(&bingbot).unwrap().user_agents, ["*"]);
+/// assert!(!robots.is_allowed(&bingbot, "/index.html"));
+/// ```
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Default)]
+pub struct RobotsTxt {
+    pub groups: Vec<RobotsGroup>,
+}
+
+/// A `user-agent` group with its rules
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Default)]
+pub struct RobotsGroup {
+    /// The product tokens of the `user-agent` lines, as written
+    pub user_agents: Vec<String>,
+    pub rules: Vec<RobotsRule>,
+}
+
+/// An `allow` or `disallow` rule
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+pub struct RobotsRule {
+    pub allow: bool,
+    /// The path pattern, where `*` matches any sequence and a trailing `$` anchors
+    /// the end of the path
+    pub pattern: String,
+}
+
+impl RobotsTxt {
+    /// Parses the content of a robots.txt file, ignoring a leading byte order mark
+    pub fn parse(content: &str) -> RobotsTxt {
+        let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+        let mut groups: Vec<RobotsGroup> = Vec::new();
+        // Consecutive user-agent lines share a group, a rule line closes its header
+        let mut in_header = false;
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if !in_header {
+                        groups.push(RobotsGroup::default());
+                        in_header = true;
+                    }
+                    let token = value.split('/').next().unwrap_or_default().trim();
+                    if let Some(group) = groups.last_mut() {
+                        group.user_agents.push(token.to_string());
+                    }
+                }
+                key @ ("allow" | "disallow") => {
+                    in_header = false;
+                    // An empty rule matches nothing
+                    if let Some(group) = groups.last_mut()
+                        && !value.is_empty()
+                    {
+                        group.rules.push(RobotsRule {
+                            allow: key == "allow",
+                            pattern: value.to_string(),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        RobotsTxt { groups }
+    }
+
+    /// The group that applies to a crawler with the given product token
+    ///
+    /// Groups naming the token, compared case-insensitively, are merged into one.
+    /// Without such a group the `*` groups apply, and without those there is no
+    /// group and everything is allowed.
+    pub fn group_for_token(&self, product_token: &str) -> Option<RobotsGroup> {
+        let named = |name: &str| -> Vec<&RobotsGroup> {
+            self.groups
+                .iter()
+                .filter(|group| {
+                    group
+                        .user_agents
+                        .iter()
+                        .any(|agent| agent.eq_ignore_ascii_case(name))
+                })
+                .collect()
+        };
+        let mut matching = named(product_token);
+        if matching.is_empty() {
+            matching = named("*");
+        }
+        if matching.is_empty() {
+            return None;
+        }
+        let mut merged = RobotsGroup::default();
+        for group in matching {
+            merged.user_agents.extend(group.user_agents.iter().cloned());
+            merged.rules.extend(group.rules.iter().cloned());
+        }
+        merged.user_agents.dedup();
+        Some(merged)
+    }
+
+    /// The group that applies to a parsed user agent
+    ///
+    /// Known bots are matched by their [`Bot::robots_token`](crate::Bot::robots_token).
+    /// Other clients fall under the `*` groups.
+    pub fn group_for(&self, info: &UserAgentInfo) -> Option<RobotsGroup> {
+        let token = info
+            .bot
+            .as_ref()
+            .and_then(|bot| bot.robots_token.as_deref())
+            .unwrap_or("*");
+        self.group_for_token(token)
+    }
+
+    /// Whether the user agent may crawl `path`, which may include a query string
+    pub fn is_allowed(&self, info: &UserAgentInfo, path: &str) -> bool {
+        self.group_for(info)
+            .is_none_or(|group| group.is_allowed(path))
+    }
+}
+
+impl RobotsGroup {
+    /// The rule deciding `path`: the matching rule with the longest pattern, with
+    /// `allow` winning a tie
+    pub fn matching_rule(&self, path: &str) -> Option<&RobotsRule> {
+        let path = normalize(path);
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                let pattern = normalize(&rule.pattern);
+                matches(&pattern, &path).then_some((pattern.len(), rule.allow, rule))
+            })
+            .max_by_key(|&(length, allow, _)| (length, allow))
+            .map(|(.., rule)| rule)
+    }
+
+    /// Whether the group allows `path`. `/robots.txt` is always allowed
+    pub fn is_allowed(&self, path: &str) -> bool {
+        path == "/robots.txt" || self.matching_rule(path).is_none_or(|rule| rule.allow)
+    }
+}
+
+/// Percent-encodes the bytes that must be encoded in a URI, and uppercases existing
+/// escapes, so patterns and paths compare octet by octet
+fn normalize(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut normalized = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len()
+                && bytes[i + 1].is_ascii_hexdigit()
+                && bytes[i + 2].is_ascii_hexdigit() =>
+            {
+                normalized.push('%');
+                normalized.push(bytes[i + 1].to_ascii_uppercase() as char);
+                normalized.push(bytes[i + 2].to_ascii_uppercase() as char);
+                i += 2;
+            }
+            b if b.is_ascii_graphic() => normalized.push(b as char),
+            b => normalized.push_str(&format!("%{:02X}", b)),
+        }
+        i += 1;
+    }
+    normalized
+}
+
+/// Whether `pattern` matches the start of `path`, or all of it with a trailing `$`
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return !anchored || rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    match anchored {
+        true => rest.ends_with(last),
+        false => rest.contains(last),
+    }
+}
@@ -0,0 +1,289 @@
+//! User-supplied detection rules layered on top of the built-in ones.
+
+use crate::frozen::is_reduced_version;
+use crate::token::Haystack;
+#[cfg(feature = "uap")]
+use crate::uap::UapRules;
+use crate::{
+    Browser, Confidence, DeviceType, Error, Evidence, OperatingSystem, Source, UserAgentInfo,
+    UserAgentParser, Version,
+};
+use regex::{Regex, RegexBuilder};
+use std::cmp::Reverse;
+
+/// Default limit on the compiled size of a single rule pattern
+const DEFAULT_SIZE_LIMIT: usize = 256 * 1024;
+/// Default limit on the length of a single rule pattern
+const DEFAULT_MAX_PATTERN_LENGTH: usize = 4096;
+
+/// A custom detection rule: a pattern, the fields it sets and a priority
+///
+/// Patterns are case-insensitive regular expressions. A named `version` group sets
+/// the browser version and a named `os_version` group sets the OS version.
+///
+/// Built-in detection has priority `0`. Rules with a higher priority override
+/// whatever the built-in rules detected, rules with a priority of `0` or less only
+/// fill fields the built-in rules left unknown. Among custom rules, the highest
+/// priority match wins for each field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pattern: String,
+    os: Option<OperatingSystem>,
+    browser: Option<Browser>,
+    device_type: Option<DeviceType>,
+    priority: i32,
+}
+
+impl Rule {
+    pub fn new(pattern: impl Into<String>) -> Rule {
+        Rule {
+            pattern: pattern.into(),
+            os: None,
+            browser: None,
+            device_type: None,
+            priority: 0,
+        }
+    }
+
+    pub fn os(mut self, os: OperatingSystem) -> Rule {
+        self.os = Some(os);
+        self
+    }
+
+    pub fn browser(mut self, browser: Browser) -> Rule {
+        self.browser = Some(browser);
+        self
+    }
+
+    pub fn device_type(mut self, device_type: DeviceType) -> Rule {
+        self.device_type = Some(device_type);
+        self
+    }
+
+    pub fn priority(mut self, priority: i32) -> Rule {
+        self.priority = priority;
+        self
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct CompiledRule {
+    regex: Regex,
+    rule: Rule,
+}
+
+/// Builds a [`UserAgentParser`] from the built-in rules plus custom rules
+///
+/// # Example
+///
+/// ```
+/// use agent_parser_ro::{Browser, DeviceType, Rule, UserAgentParser};
+///
+/// let parser = UserAgentParser::builder()
+///     .rule(
+///         Rule::new(r"AcmeApp/(?P<version>[\d.]+)")
+///             .browser(Browser::Other("AcmeApp".to_string()))
+///             .device_type(DeviceType::Mobile)
+///             .priority(10),
+///     )
+///     .build()
+///     .unwrap();
+///
+/// let info = parser.detect("AcmeApp/3.2.1 (Android 13; SM-A536B)");
+/// assert_eq!(info.browser, Browser::Other("AcmeApp".to_string()));
+/// assert_eq!(info.browser_version.unwrap().raw, "3.2.1");
+/// ```
+#[derive(Debug, Clone)]
+pub struct UserAgentParserBuilder {
+    rules: Vec<Rule>,
+    size_limit: usize,
+    max_pattern_length: usize,
+    #[cfg(feature = "uap")]
+    uap: Option<UapRules>,
+}
+
+impl Default for UserAgentParserBuilder {
+    fn default() -> Self {
+        UserAgentParserBuilder {
+            rules: Vec::new(),
+            size_limit: DEFAULT_SIZE_LIMIT,
+            max_pattern_length: DEFAULT_MAX_PATTERN_LENGTH,
+            #[cfg(feature = "uap")]
+            uap: None,
+        }
+    }
+}
+
+impl UserAgentParserBuilder {
+    /// Adds a custom rule
+    pub fn rule(mut self, rule: Rule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Adds several custom rules
+    pub fn rules(mut self, rules: impl IntoIterator<Item = Rule>) -> Self {
+        self.rules.extend(rules);
+        self
+    }
+
+    /// Limits the compiled size of each rule pattern, in bytes
+    pub fn size_limit(mut self, bytes: usize) -> Self {
+        self.size_limit = bytes;
+        self
+    }
+
+    /// Limits the length of each rule pattern, in bytes
+    pub fn max_pattern_length(mut self, length: usize) -> Self {
+        self.max_pattern_length = length;
+        self
+    }
+
+    /// Loads rules from a uap-core `regexes.yaml` file
+    ///
+    /// Matches from these rules take precedence over the built-in detection, while
+    /// client hints and custom rules with a positive priority still override them.
+    #[cfg(feature = "uap")]
+    pub fn uap_rules(mut self, rules: UapRules) -> Self {
+        self.uap = Some(rules);
+        self
+    }
+
+    /// Compiles the rules, failing on the first invalid or oversized pattern
+    pub fn build(self) -> Result<UserAgentParser, Error> {
+        let mut rules = self
+            .rules
+            .iter()
+            .map(|rule| {
+                Ok(CompiledRule {
+                    regex: self.compile(&rule.pattern, true)?,
+                    rule: rule.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        // Stable, so rules with the same priority keep their insertion order
+        rules.sort_by_key(|compiled| Reverse(compiled.rule.priority));
+
+        Ok(UserAgentParser {
+            rules,
+            #[cfg(feature = "uap")]
+            uap: self
+                .uap
+                .as_ref()
+                .map(|uap| uap.compile(&self))
+                .transpose()?,
+        })
+    }
+
+    /// Compiles a single pattern within the configured limits
+    pub(crate) fn compile(&self, pattern: &str, case_insensitive: bool) -> Result<Regex, Error> {
+        self.check_length(pattern)?;
+        RegexBuilder::new(pattern)
+            .case_insensitive(case_insensitive)
+            .size_limit(self.size_limit)
+            .dfa_size_limit(self.size_limit)
+            .build()
+            .map_err(|source| Error::InvalidPattern {
+                pattern: pattern.to_string(),
+                source,
+            })
+    }
+
+    /// Compiles a single pattern with ASCII classes, for matching raw bytes
+    ///
+    /// Rule files written for other engines expect `.`, `\d` and `\w` to work on
+    /// ASCII. With Unicode classes, a repetition such as `.{0,300}` compiles to well
+    /// over the default size limit.
+    #[cfg(feature = "uap")]
+    pub(crate) fn compile_ascii(
+        &self,
+        pattern: &str,
+        case_insensitive: bool,
+    ) -> Result<regex::bytes::Regex, Error> {
+        self.check_length(pattern)?;
+        regex::bytes::RegexBuilder::new(pattern)
+            .unicode(false)
+            .case_insensitive(case_insensitive)
+            .size_limit(self.size_limit)
+            .dfa_size_limit(self.size_limit)
+            .build()
+            .map_err(|source| Error::InvalidPattern {
+                pattern: pattern.to_string(),
+                source,
+            })
+    }
+
+    fn check_length(&self, pattern: &str) -> Result<(), Error> {
+        if pattern.len() > self.max_pattern_length {
+            return Err(Error::PatternTooLong {
+                pattern: pattern.to_string(),
+                limit: self.max_pattern_length,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Applies custom rules to a result of the built-in detection
+pub(crate) fn apply(rules: &[CompiledRule], ua: &str, info: &mut UserAgentInfo) {
+    let (mut os_set, mut browser_set, mut device_type_set) = (false, false, false);
+
+    for CompiledRule { regex, rule } in rules {
+        let Some(caps) = regex.captures(ua) else {
+            continue;
+        };
+        let overrides = rule.priority > 0;
+        let matched = caps.get(0).map_or(0..0, |m| m.range());
+        let evidence = || {
+            let source = Source::CustomRule {
+                pattern: rule.pattern.clone(),
+            };
+            let confidence = if overrides {
+                Confidence::High
+            } else {
+                Confidence::Medium
+            };
+            Some(Evidence::matched(ua, matched.clone(), source, confidence))
+        };
+
+        if let Some(os) = &rule.os
+            && !os_set
+            && (overrides || info.os == OperatingSystem::Unknown)
+        {
+            info.os = os.clone();
+            info.os_version = caps
+                .name("os_version")
+                .and_then(|m| Version::parse(m.as_str()))
+                .or_else(|| os.detect_version(&Haystack::new(ua)));
+            info.os_release = os.release(info.os_version.as_ref());
+            info.frozen.os_version = false;
+            info.frozen.os_version_at_least = None;
+            info.evidence.os = evidence();
+            os_set = true;
+        }
+        if let Some(browser) = &rule.browser
+            && !browser_set
+            && (overrides || info.browser == Browser::Unknown)
+        {
+            info.browser = browser.clone();
+            info.browser_version = caps
+                .name("version")
+                .and_then(|m| Version::parse(m.as_str()))
+                .or_else(|| browser.detect_version(&Haystack::new(ua)));
+            info.frozen.browser_version = info
+                .browser_version
+                .as_ref()
+                .is_some_and(is_reduced_version);
+            info.evidence.browser = evidence();
+            browser_set = true;
+        }
+        if let Some(device_type) = &rule.device_type
+            && !device_type_set
+            && (overrides || info.device_type == DeviceType::Unknown)
+        {
+            info.device_type = device_type.clone();
+            info.evidence.device_type = evidence();
+            device_type_set = true;
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! The layers of software behind an in-app browser or web view.
+//!
+//! A link opened in the Facebook app on iOS renders in a WKWebView hosted by the
+//! app, on Apple's WebKit. The flat `browser` field can name only one of those;
+//! the stack keeps each layer with its own version.
+
+use crate::token::Scope::{Product, Text};
+use crate::token::{Haystack, Keywords, Scope, Token};
+use crate::{Browser, Engine, OperatingSystem, Version};
+use serde::{Deserialize, Serialize};
+
+/// An app that opens web pages inside itself
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum App {
+    Facebook,
+    Instagram,
+    TikTok,
+    Line,
+    WeChat,
+    Snapchat,
+    /// The Google app, also known as the Google Search App (GSA)
+    GoogleApp,
+    Pinterest,
+    LinkedIn,
+    Twitter,
+}
+
+impl App {
+    /// Tokens that carry this app's version, in order of preference
+    fn version_tokens(&self) -> &'static [&'static str] {
+        match self {
+            App::Facebook => &["FBAV/"],
+            App::Instagram => &["Instagram "],
+            App::TikTok => &["app_version/", "musical_ly_"],
+            App::Line => &["Line/"],
+            App::WeChat => &["MicroMessenger/"],
+            App::Snapchat => &["Snapchat/"],
+            App::GoogleApp => &["GSA/"],
+            App::Pinterest => &["Pinterest for Android/", "Pinterest for iOS/"],
+            App::LinkedIn => &["LinkedInApp]/"],
+            App::Twitter => &["Twitter for iPhone/", "TwitterAndroid/"],
+        }
+    }
+}
+
+/// `(markers, app)`, checked in order
+///
+/// Instagram and TikTok come first because their user agents may carry tokens of
+/// the SDKs they embed.
+#[rustfmt::skip]
+const HOST_APPS: &[(&[(&str, Scope)], App)] = &[
+    (&[("instagram", Product)], App::Instagram),
+    (&[("musical_ly_", Product), ("trill_", Product), ("bytedancewebview", Product)], App::TikTok),
+    (&[("fban/", Text), ("fbav/", Text), ("fb_iab/", Text)], App::Facebook),
+    (&[("line", Product)], App::Line),
+    (&[("micromessenger", Product)], App::WeChat),
+    (&[("snapchat", Product)], App::Snapchat),
+    (&[("gsa", Product)], App::GoogleApp),
+    (
+        &[("pinterest for android", Product), ("pinterest for ios", Product), ("[pinterest/", Text)],
+        App::Pinterest,
+    ),
+    (&[("[linkedinapp]", Text)], App::LinkedIn),
+    (&[("twitter for iphone", Product), ("twitterandroid", Product)], App::Twitter),
+];
+
+/// The host app layer of a client stack
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+pub struct HostApp {
+    pub app: App,
+    pub version: Option<Version>,
+}
+
+/// How a host app embeds the browser
+///
+/// Android Custom Tabs and iOS SFSafariViewController send the browser's own user
+/// agent, so they cannot be told apart from the browser itself.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Embedding {
+    /// An Android WebView or iOS WKWebView, rendering inside the app
+    WebView,
+}
+
+/// The host app, embedding and browser engine behind a user agent, each with its
+/// own version
+///
+/// For an ordinary browser or a bot `host` and `embedding` are `None` and the
+/// browser layer matches the parse result.
+///
+/// # Example
+///
+/// ```
+/// use agent_parser_ro::{App, Browser, Embedding, Engine, UserAgentParser};
+///
+/// let info = UserAgentParser::parse("Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Mobile/15E148 [FBAN/FBIOS;FBAV/441.0.0.23.105;FBBV/548811442;FBDV/iPhone14,5;FBMD/iPhone;FBSN/iOS;FBSV/17.1;FBSS/3;FBCR/;FBID/phone;FBLC/en_US;FBOP/5]");
+/// let stack = info.client_stack;
+///
+/// let host = stack.host.unwrap();
+/// assert_eq!(host.app, App::Facebook);
+/// assert_eq!(host.version.unwrap().major, 441);
+/// assert_eq!(stack.embedding, Some(Embedding::WebView));
+/// assert_eq!(stack.browser, Browser::Safari);
+/// assert_eq!(stack.engine, Engine::WebKit);
+/// assert_eq!(stack.engine_version.unwrap().raw, "605.1.15");
+/// ```
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct ClientStack {
+    /// The app hosting the page, for in-app browsers
+    pub host: Option<HostApp>,
+    pub embedding: Option<Embedding>,
+    /// The browser the page renders in: Chrome for Android web views, Safari on iOS
+    pub browser: Browser,
+    pub browser_version: Option<Version>,
+    pub engine: Engine,
+    pub engine_version: Option<Version>,
+}
+
+impl ClientStack {
+    /// Splits the client into layers, given the flat parse result
+    ///
+    /// Bots mentioning an app, such as Pinterestbot, keep a single layer.
+    pub(crate) fn detect(
+        haystack: &Haystack,
+        bot: bool,
+        os: &OperatingSystem,
+        browser: &Browser,
+        browser_version: Option<&Version>,
+        engine: &Engine,
+        engine_version: Option<&Version>,
+    ) -> ClientStack {
+        let single = ClientStack {
+            host: None,
+            embedding: None,
+            browser: browser.clone(),
+            browser_version: browser_version.cloned(),
+            engine: engine.clone(),
+            engine_version: engine_version.cloned(),
+        };
+        if bot {
+            return single;
+        }
+
+        let host = HOST_APPS
+            .iter()
+            .find(|(markers, _)| Keywords(markers).find(haystack).is_some())
+            .map(|&(_, app)| HostApp {
+                app,
+                // Build numbers such as `musical_ly_2023109040` are not versions
+                version: app
+                    .version_tokens()
+                    .iter()
+                    .filter_map(|token| haystack.token_version(token))
+                    .find(|version| version.minor.is_some()),
+            });
+
+        let tokens = haystack.tokens();
+        let product = |name: &str| {
+            tokens
+                .iter()
+                .any(|token| matches!(token, Token::Product(product) if product.name == name))
+        };
+        let apple = matches!(os, OperatingSystem::IOS | OperatingSystem::IPadOS);
+        let embedding = if *os == OperatingSystem::Android {
+            let wv = tokens.iter().any(|token| match token {
+                Token::Comment(comment) => comment.entries().any(|(entry, _)| entry == "wv"),
+                Token::Product(_) => false,
+            });
+            let legacy = product("Chrome") && haystack.ua().contains("Version/4.0");
+            (wv || legacy).then_some(Embedding::WebView)
+        } else if apple {
+            // An app token on Safari's full user agent says nothing about the embedding
+            let safari = product("Safari") && product("Version");
+            match &host {
+                Some(_) if !safari => Some(Embedding::WebView),
+                None if !product("Safari") && product("Mobile") => Some(Embedding::WebView),
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        let (browser, browser_version) = match (&host, embedding) {
+            (None, None) => return single,
+            _ if product("Chrome") => (Browser::Chrome, haystack.token_version("Chrome/")),
+            _ if apple => (Browser::Safari, haystack.token_version("Version/")),
+            _ => (single.browser.clone(), single.browser_version.clone()),
+        };
+
+        ClientStack {
+            host,
+            embedding,
+            browser,
+            browser_version,
+            ..single
+        }
+    }
+}
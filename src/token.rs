@@ -0,0 +1,299 @@
+//! Splits user agents into product and comment tokens, and matches detection
+//! keywords against them.
+//!
+//! A user agent is a sequence of products such as `Chrome/120.0` and parenthesised
+//! comments such as `(Linux; Android 14)`. Matching keywords against the token they
+//! belong to keeps short keywords like `tor` or `tv` from firing inside unrelated
+//! words like "Motorola".
+
+use crate::Version;
+use std::ops::Range;
+
+/// A product or comment token of a user agent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token<'a> {
+    Product(Product<'a>),
+    Comment(Comment<'a>),
+}
+
+impl Token<'_> {
+    /// The byte range of the token in the user agent
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Token::Product(product) => product.span.clone(),
+            Token::Comment(comment) => comment.span.clone(),
+        }
+    }
+}
+
+/// A `name/version` product token such as `Chrome/120.0.0.0`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Product<'a> {
+    /// The product name, such as `Chrome`
+    pub name: &'a str,
+    /// The text after the first `/`, if any
+    pub version: Option<&'a str>,
+    /// The byte range of the whole token in the user agent
+    pub span: Range<usize>,
+}
+
+/// A parenthesised comment such as `(Linux; Android 14)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment<'a> {
+    /// The text between the parentheses
+    pub text: &'a str,
+    /// The byte range of the comment in the user agent, including the parentheses
+    pub span: Range<usize>,
+}
+
+impl<'a> Comment<'a> {
+    /// Iterates over the `;` separated entries of the comment, trimmed of whitespace,
+    /// with their byte ranges in the user agent
+    ///
+    /// Empty entries are skipped, and separators inside nested comments or escaped
+    /// with a backslash do not split.
+    pub fn entries(&self) -> impl Iterator<Item = (&'a str, Range<usize>)> + 'a {
+        let text = self.text;
+        let offset = self.span.start + 1;
+        let bytes = text.as_bytes();
+        let mut depth = 0usize;
+        let mut start = 0;
+        let mut bounds = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' => i += 1,
+                b'(' => depth += 1,
+                b')' => depth = depth.saturating_sub(1),
+                b';' if depth == 0 => {
+                    bounds.push(start..i);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        bounds.push(start..text.len());
+        bounds.into_iter().filter_map(move |range| {
+            let entry = &text[range.clone()];
+            let trimmed = entry.trim_start();
+            let start = range.start + entry.len() - trimmed.len();
+            let trimmed = trimmed.trim_end();
+            (!trimmed.is_empty()).then(|| (trimmed, offset + start..offset + start + trimmed.len()))
+        })
+    }
+}
+
+/// Splits a user agent into its `product/version` tokens and parenthesised comments,
+/// in order of appearance
+///
+/// Products are separated by whitespace. Comments may nest, a backslash escapes
+/// the next character inside a comment, and an unclosed comment runs to the end of
+/// the string. Token text borrows from the user agent.
+///
+/// # Example
+///
+/// ```
+/// use agent_parser_ro::{tokenize, Token};
+///
+/// let ua = "Mozilla/5.0 (X11; Linux x86_64) myapp/1.0.0 Electron/28.1.0";
+/// let tokens = tokenize(ua);
+///
+/// let Token::Comment(comment) = &tokens[1] else { panic!() };
+/// let entries: Vec<_> = comment.entries().map(|(entry, _)| entry).collect();
+/// assert_eq!(entries, ["X11", "Linux x86_64"]);
+///
+/// let Token::Product(app) = &tokens[2] else { panic!() };
+/// assert_eq!((app.name, app.version), ("myapp", Some("1.0.0")));
+/// assert_eq!(&ua[app.span.clone()], "myapp/1.0.0");
+/// ```
+pub fn tokenize(ua: &str) -> Vec<Token<'_>> {
+    let bytes = ua.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b if b.is_ascii_whitespace() => i += 1,
+            b'(' => {
+                let start = i;
+                let mut depth = 0;
+                while i < bytes.len() {
+                    match bytes[i] {
+                        b'\\' => i += 1,
+                        b'(' => depth += 1,
+                        b')' => depth -= 1,
+                        _ => {}
+                    }
+                    i = (i + 1).min(bytes.len());
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                let end = if depth == 0 { i - 1 } else { i };
+                tokens.push(Token::Comment(Comment {
+                    text: &ua[start + 1..end],
+                    span: start..i,
+                }));
+            }
+            _ => {
+                let start = i;
+                while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'(' {
+                    i += 1;
+                }
+                let text = &ua[start..i];
+                let (name, version) = match text.split_once('/') {
+                    Some((name, version)) => (name, Some(version)),
+                    None => (text, None),
+                };
+                tokens.push(Token::Product(Product {
+                    name,
+                    version,
+                    span: start..i,
+                }));
+            }
+        }
+    }
+    tokens
+}
+
+/// Where in a user agent a detection keyword may match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Scope {
+    /// The start of a product name, such as `Chrome` in `Chrome/120.0`
+    Product,
+    /// A word inside a comment, such as `Android` in `(Linux; Android 14)`
+    Comment,
+    /// Anywhere, for keywords distinctive enough to never occur by accident
+    Text,
+}
+
+/// A user agent prepared for keyword matching
+///
+/// The user agent is tokenized and lowercased once per parse, and every detector
+/// reads from the same haystack.
+pub(crate) struct Haystack<'a> {
+    ua: &'a str,
+    lower: String,
+    tokens: Vec<Token<'a>>,
+}
+
+impl<'a> Haystack<'a> {
+    pub(crate) fn new(ua: &'a str) -> Self {
+        Haystack {
+            ua,
+            lower: ua.to_ascii_lowercase(),
+            tokens: tokenize(ua),
+        }
+    }
+
+    /// The user agent as given
+    pub(crate) fn ua(&self) -> &'a str {
+        self.ua
+    }
+
+    /// The user agent in ASCII lowercase, with the same byte offsets
+    pub(crate) fn lower(&self) -> &str {
+        &self.lower
+    }
+
+    /// The product and comment tokens of the user agent
+    pub(crate) fn tokens(&self) -> &[Token<'a>] {
+        &self.tokens
+    }
+
+    /// Finds the version that follows `token` (e.g. `"Chrome/"` or `"MSIE "`)
+    ///
+    /// Matching is case-insensitive and only accepts occurrences that start on a word
+    /// boundary, so `"Chrome/"` does not match inside `"HeadlessChrome/"`.
+    pub(crate) fn token_version(&self, token: &str) -> Option<Version> {
+        let bytes = self.lower.as_bytes();
+        let needle = token.as_bytes();
+        (0..=bytes.len().checked_sub(needle.len())?)
+            .filter(|&start| bytes[start..start + needle.len()].eq_ignore_ascii_case(needle))
+            .filter(|&start| start == 0 || !bytes[start - 1].is_ascii_alphanumeric())
+            .find_map(|start| Version::parse(&self.ua[start + needle.len()..]))
+    }
+
+    /// The spans where `keyword` occurs in the part of the user agent its scope
+    /// looks at, in order
+    fn occurrences<'k>(
+        &'k self,
+        keyword: &'k str,
+        scope: Scope,
+    ) -> Box<dyn Iterator<Item = Range<usize>> + 'k> {
+        let within = move |span: Range<usize>| {
+            self.lower[span.clone()]
+                .match_indices(keyword)
+                .map(move |(start, _)| span.start + start..span.start + start + keyword.len())
+        };
+        match scope {
+            Scope::Text => Box::new(within(0..self.lower.len())),
+            Scope::Product => Box::new(self.tokens.iter().filter_map(move |token| match token {
+                Token::Product(product) => {
+                    let start = product.span.start;
+                    self.lower[start..]
+                        .starts_with(keyword)
+                        .then(|| start..start + keyword.len())
+                }
+                Token::Comment(_) => None,
+            })),
+            Scope::Comment => Box::new(
+                self.tokens
+                    .iter()
+                    .filter_map(|token| match token {
+                        Token::Comment(comment) => Some(comment.span.clone()),
+                        Token::Product(_) => None,
+                    })
+                    .flat_map(within),
+            ),
+        }
+    }
+
+    /// Whether `span` may hold a keyword of the given scope
+    ///
+    /// Product and comment keywords must start a word and must not run into
+    /// further letters, though digits may follow as in `SM-T970` or `PS4`.
+    fn allows(&self, span: &Range<usize>, scope: Scope) -> bool {
+        let bytes = self.lower.as_bytes();
+        let starts_word = span.start == 0 || !bytes[span.start - 1].is_ascii_alphanumeric();
+        let ends_word = span.end == bytes.len() || !bytes[span.end].is_ascii_alphabetic();
+        match scope {
+            Scope::Text => true,
+            Scope::Product => ends_word
+                && self.tokens.iter().any(
+                    |token| matches!(token, Token::Product(product) if product.span.start == span.start),
+                ),
+            Scope::Comment => {
+                starts_word
+                    && ends_word
+                    && self.tokens.iter().any(|token| {
+                        matches!(token, Token::Comment(comment)
+                            if comment.span.start < span.start && span.end <= comment.span.end)
+                    })
+            }
+        }
+    }
+}
+
+/// An ordered set of lowercase keywords, each limited to a scope
+pub(crate) struct Keywords(pub(crate) &'static [(&'static str, Scope)]);
+
+impl Keywords {
+    /// Finds the leftmost keyword allowed by its scope
+    ///
+    /// When several keywords match at the same position the one listed first wins,
+    /// like an alternation in a regex.
+    pub(crate) fn find(&self, haystack: &Haystack) -> Option<(&'static str, Range<usize>)> {
+        self.0
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &(keyword, scope))| {
+                haystack
+                    .occurrences(keyword, scope)
+                    .find(|span| haystack.allows(span, scope))
+                    .map(|span| (span.start, index, keyword, span))
+            })
+            .min_by_key(|&(start, index, ..)| (start, index))
+            .map(|(_, _, keyword, span)| (keyword, span))
+    }
+}
@@ -0,0 +1,340 @@
+//! Rules in the uap-core `regexes.yaml` format.
+//!
+//! The file holds three ordered lists, `user_agent_parsers`, `os_parsers` and
+//! `device_parsers`. For each list the first matching regex wins, and its
+//! replacement templates may refer to capture groups as `$1` to `$9`.
+
+use crate::frozen::is_reduced_version;
+use crate::token::Haystack;
+use crate::{
+    Browser, Confidence, DeviceType, Error, Evidence, FrozenFields, OperatingSystem, Source,
+    UserAgentInfo, UserAgentParserBuilder, Version,
+};
+use regex::bytes::{Captures, Regex};
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+struct UserAgentRule {
+    regex: String,
+    regex_flag: Option<String>,
+    family_replacement: Option<String>,
+    v1_replacement: Option<String>,
+    v2_replacement: Option<String>,
+    v3_replacement: Option<String>,
+    v4_replacement: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+struct OsRule {
+    regex: String,
+    regex_flag: Option<String>,
+    os_replacement: Option<String>,
+    os_v1_replacement: Option<String>,
+    os_v2_replacement: Option<String>,
+    os_v3_replacement: Option<String>,
+    os_v4_replacement: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+struct DeviceRule {
+    regex: String,
+    regex_flag: Option<String>,
+    device_replacement: Option<String>,
+    brand_replacement: Option<String>,
+    model_replacement: Option<String>,
+}
+
+/// The rules of a uap-core `regexes.yaml` file, ready to be passed to
+/// [`UserAgentParserBuilder::uap_rules`]
+///
+/// The crate does not bundle a copy of the file. Embed one with `include_str!` and
+/// [`UapRules::from_yaml`], or read it at runtime with [`UapRules::from_path`].
+///
+/// uap-core device families mostly name a brand and model rather than a form factor.
+/// Only `Spider`, the Apple families and the generic smartphone, feature phone and
+/// tablet families set the device type. Other families are kept as
+/// [`DeviceType::Other`] when the built-in detection found no form factor.
+///
+/// Patterns are compiled with ASCII classes, as uap-core expects.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct UapRules {
+    user_agent_parsers: Vec<UserAgentRule>,
+    os_parsers: Vec<OsRule>,
+    device_parsers: Vec<DeviceRule>,
+}
+
+impl UapRules {
+    /// Parses the contents of a `regexes.yaml` file
+    pub fn from_yaml(yaml: &str) -> Result<UapRules, Error> {
+        serde_norway::from_str(yaml).map_err(Error::Yaml)
+    }
+
+    /// Reads and parses a `regexes.yaml` file from disk
+    pub fn from_path(path: impl AsRef<Path>) -> Result<UapRules, Error> {
+        let yaml = std::fs::read_to_string(path).map_err(Error::Io)?;
+        UapRules::from_yaml(&yaml)
+    }
+
+    pub(crate) fn compile(&self, builder: &UserAgentParserBuilder) -> Result<CompiledUap, Error> {
+        let compile = |pattern: &str, flag: &Option<String>| {
+            builder.compile_ascii(pattern, flag.as_deref() == Some("i"))
+        };
+        Ok(CompiledUap {
+            user_agent: self
+                .user_agent_parsers
+                .iter()
+                .map(|rule| Ok((compile(&rule.regex, &rule.regex_flag)?, rule.clone())))
+                .collect::<Result<_, Error>>()?,
+            os: self
+                .os_parsers
+                .iter()
+                .map(|rule| Ok((compile(&rule.regex, &rule.regex_flag)?, rule.clone())))
+                .collect::<Result<_, Error>>()?,
+            device: self
+                .device_parsers
+                .iter()
+                .map(|rule| Ok((compile(&rule.regex, &rule.regex_flag)?, rule.clone())))
+                .collect::<Result<_, Error>>()?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct CompiledUap {
+    user_agent: Vec<(Regex, UserAgentRule)>,
+    os: Vec<(Regex, OsRule)>,
+    device: Vec<(Regex, DeviceRule)>,
+}
+
+impl CompiledUap {
+    /// Overrides the built-in detection with the first match of each rule list
+    pub(crate) fn apply(&self, ua: &str, info: &mut UserAgentInfo) {
+        if let Some((caps, rule)) = first_match(&self.user_agent, ua) {
+            let family = expand(&rule.family_replacement, &caps, 1);
+            let browser = family
+                .as_deref()
+                .map(family_browser)
+                .unwrap_or(Browser::Unknown);
+            if browser != Browser::Unknown {
+                info.browser = browser;
+                info.evidence.browser = Some(evidence(ua, &caps, &rule.regex));
+                info.browser_version = join_version([
+                    expand(&rule.v1_replacement, &caps, 2),
+                    expand(&rule.v2_replacement, &caps, 3),
+                    expand(&rule.v3_replacement, &caps, 4),
+                    expand(&rule.v4_replacement, &caps, 5),
+                ]);
+                info.frozen.browser_version = info
+                    .browser_version
+                    .as_ref()
+                    .is_some_and(is_reduced_version);
+            }
+        }
+
+        if let Some((caps, rule)) = first_match(&self.os, ua) {
+            let family = expand(&rule.os_replacement, &caps, 1);
+            let os = family
+                .as_deref()
+                .map(family_os)
+                .unwrap_or(OperatingSystem::Unknown);
+            // uap-core reports iPadOS as iOS
+            if os != OperatingSystem::Unknown
+                && !(os == OperatingSystem::IOS && info.os == OperatingSystem::IPadOS)
+            {
+                info.os = os;
+                info.evidence.os = Some(evidence(ua, &caps, &rule.regex));
+                let v1 = expand(&rule.os_v1_replacement, &caps, 2);
+                if info.os == OperatingSystem::Windows {
+                    // uap-core names Windows releases, such as `XP` or `7`, rather than
+                    // their NT versions
+                    info.os_version = info.os.detect_version(&Haystack::new(ua));
+                    info.os_release = v1;
+                } else {
+                    info.os_version = join_version([
+                        v1,
+                        expand(&rule.os_v2_replacement, &caps, 3),
+                        expand(&rule.os_v3_replacement, &caps, 4),
+                        expand(&rule.os_v4_replacement, &caps, 5),
+                    ]);
+                    info.os_release = None;
+                }
+                let frozen = FrozenFields::detect(
+                    &info.os,
+                    info.os_version.as_ref(),
+                    None,
+                    None,
+                    None,
+                    &info.device,
+                );
+                info.frozen.os_version = frozen.os_version;
+                info.frozen.os_version_at_least = frozen.os_version_at_least;
+            }
+        }
+
+        if let Some((caps, rule)) = first_match(&self.device, ua) {
+            let family = expand(&rule.device_replacement, &caps, 1);
+            let device_type = family
+                .as_deref()
+                .map(family_device_type)
+                .unwrap_or(DeviceType::Unknown);
+            // A brand and model family is kept only where nothing else found a form factor
+            let fills = match device_type {
+                DeviceType::Unknown => false,
+                DeviceType::Other(_) => info.device_type == DeviceType::Unknown,
+                _ => true,
+            };
+            if fills {
+                info.device_type = device_type;
+                info.evidence.device_type = Some(evidence(ua, &caps, &rule.regex));
+            }
+            let brand = expand(&rule.brand_replacement, &caps, 0);
+            let model = expand(&rule.model_replacement, &caps, 1);
+            if brand.is_some() || model.is_some() {
+                info.device.brand = brand;
+                info.device.model.clone_from(&model);
+                info.device.model_code = model;
+            }
+        }
+    }
+}
+
+fn first_match<'a, 'u, T>(rules: &'a [(Regex, T)], ua: &'u str) -> Option<(Captures<'u>, &'a T)> {
+    rules
+        .iter()
+        .find_map(|(regex, rule)| regex.captures(ua.as_bytes()).map(|caps| (caps, rule)))
+}
+
+fn evidence(ua: &str, caps: &Captures, regex: &str) -> Evidence {
+    // ASCII classes can stop inside a multi-byte character
+    let span = caps.get(0).map_or(0..0, |m| {
+        ua.floor_char_boundary(m.start())..ua.ceil_char_boundary(m.end())
+    });
+    let rule = Source::UapRule {
+        regex: regex.to_string(),
+    };
+    Evidence::matched(ua, span, rule, Confidence::High)
+}
+
+/// Expands a replacement template, or takes capture group `default_group` when the
+/// rule has no template (`0` meaning no default)
+fn expand(template: &Option<String>, caps: &Captures, default_group: usize) -> Option<String> {
+    let value = match template {
+        Some(template) => {
+            let mut value = String::new();
+            let mut chars = template.chars().peekable();
+            while let Some(c) = chars.next() {
+                match (c, chars.peek().and_then(|d| d.to_digit(10))) {
+                    ('$', Some(group)) => {
+                        chars.next();
+                        if let Some(m) = caps.get(group as usize) {
+                            value.push_str(&String::from_utf8_lossy(m.as_bytes()));
+                        }
+                    }
+                    _ => value.push(c),
+                }
+            }
+            value
+        }
+        None if default_group > 0 => {
+            String::from_utf8_lossy(caps.get(default_group)?.as_bytes()).into_owned()
+        }
+        None => return None,
+    };
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Joins version components up to the first missing one and parses the result
+fn join_version(parts: [Option<String>; 4]) -> Option<Version> {
+    let joined = parts
+        .into_iter()
+        .map_while(|part| part)
+        .collect::<Vec<_>>()
+        .join(".");
+    Version::parse(&joined)
+}
+
+/// Maps a uap-core user agent family onto a browser, keeping unknown families
+fn family_browser(family: &str) -> Browser {
+    match family {
+        "Other" => Browser::Unknown,
+        "Chrome" | "Chrome Mobile" | "Chrome Mobile iOS" | "HeadlessChrome" => Browser::Chrome,
+        "Chrome Mobile WebView" => Browser::WebView,
+        "Safari" | "Mobile Safari" | "Mobile Safari UI/WKWebView" => Browser::Safari,
+        "Firefox" | "Firefox Mobile" | "Firefox iOS" => Browser::Firefox,
+        "Edge" | "Edge Mobile" => Browser::Edge,
+        "IE" | "IE Mobile" => Browser::InternetExplorer,
+        "Opera" | "Opera Mini" | "Opera Mobile" => Browser::Opera,
+        "Samsung Internet" => Browser::SamsungBrowser,
+        "UC Browser" => Browser::UCBrowser,
+        "Yandex Browser" => Browser::Yandex,
+        "Amazon Silk" => Browser::Silk,
+        "Oculus Browser" => Browser::OculusBrowser,
+        "DuckDuckGo Mobile" => Browser::DuckDuckGo,
+        "Yahoo! Slurp" => Browser::Yahoo,
+        "Baiduspider" => Browser::Baidu,
+        "bingbot" => Browser::Bingbot,
+        "Vivaldi" => Browser::Vivaldi,
+        "Brave" => Browser::Brave,
+        "Puffin" => Browser::Puffin,
+        "Maxthon" => Browser::Maxthon,
+        "Mercury" => Browser::Mercury,
+        "Dolphin" => Browser::Dolphin,
+        "Electron" => Browser::Electron,
+        "PhantomJS" => Browser::PhantomJS,
+        "Facebook" => Browser::Facebook,
+        "Instagram" => Browser::Instagram,
+        "Twitter" => Browser::Twitter,
+        "Snapchat" => Browser::Snapchat,
+        family if family.starts_with("Googlebot") => Browser::Googlebot,
+        family => Browser::Other(family.to_string()),
+    }
+}
+
+/// Maps a uap-core device family onto a device type, keeping unknown families
+///
+/// Most families name a brand and model, such as `Samsung SM-A536B`, and say nothing
+/// about the form factor.
+fn family_device_type(family: &str) -> DeviceType {
+    match family {
+        "Other" => DeviceType::Unknown,
+        "Spider" => DeviceType::Bot,
+        "iPhone" | "iPod" | "Generic Smartphone" | "Generic Feature Phone" => DeviceType::Mobile,
+        "iPad" | "Generic Tablet" | "Kindle" => DeviceType::Tablet,
+        "Mac" => DeviceType::Desktop,
+        "AppleTV" => DeviceType::TV,
+        family => DeviceType::Other(family.to_string()),
+    }
+}
+
+/// Maps a uap-core OS family onto an operating system, keeping unknown families
+fn family_os(family: &str) -> OperatingSystem {
+    match family {
+        "Other" => OperatingSystem::Unknown,
+        "Windows" => OperatingSystem::Windows,
+        "Windows Phone" => OperatingSystem::WindowsPhone,
+        "Mac OS X" | "macOS" => OperatingSystem::MacOS,
+        "iOS" => OperatingSystem::IOS,
+        "Android" => OperatingSystem::Android,
+        "Linux" => OperatingSystem::Linux,
+        "Ubuntu" => OperatingSystem::Ubuntu,
+        "Fedora" => OperatingSystem::Fedora,
+        "Debian" => OperatingSystem::Debian,
+        "Chrome OS" => OperatingSystem::ChromeOS,
+        "BlackBerry OS" => OperatingSystem::BlackBerry,
+        "Symbian OS" | "Symbian^3" => OperatingSystem::Symbian,
+        "webOS" => OperatingSystem::WebOS,
+        "Bada" => OperatingSystem::Bada,
+        "Tizen" => OperatingSystem::Tizen,
+        "FreeBSD" => OperatingSystem::FreeBSD,
+        "OpenBSD" => OperatingSystem::OpenBSD,
+        "Solaris" => OperatingSystem::Solaris,
+        "AIX" => OperatingSystem::AIX,
+        "HP-UX" => OperatingSystem::HPUX,
+        "KaiOS" => OperatingSystem::KaiOS,
+        "HarmonyOS" => OperatingSystem::HarmonyOS,
+        family => OperatingSystem::Other(family.to_string()),
+    }
+}
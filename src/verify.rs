@@ -0,0 +1,329 @@
+//! Checks whether a client claiming to be a known bot really belongs to its operator.
+//!
+//! A user agent is trivially spoofed, so the claim is checked against the client IP:
+//! first against the IP ranges the operator publishes, then with forward-confirmed
+//! reverse DNS. The reverse lookup must name a host under one of the operator's
+//! domains, and the forward lookup of that host must return the client IP again.
+
+use crate::{Error, UserAgentInfo};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::net::IpAddr;
+
+/// DNS lookups used for forward-confirmed reverse DNS
+///
+/// Implement it over the resolver of your choice, or over a fixed table in tests.
+pub trait Resolver {
+    /// The host names of the PTR records for `ip`
+    fn reverse(&self, ip: IpAddr) -> io::Result<Vec<String>>;
+    /// The addresses of the A and AAAA records for `host`
+    fn forward(&self, host: &str) -> io::Result<Vec<IpAddr>>;
+}
+
+/// The outcome of checking a bot's identity
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Verification {
+    /// The IP belongs to the bot's operator
+    Verified,
+    /// The operator can be checked and the IP is not theirs
+    Spoofed,
+    /// The user agent is not a known crawler, its operator publishes nothing to
+    /// check against, or a DNS lookup failed
+    Unverifiable,
+}
+
+/// An IP network in CIDR notation, such as `66.249.64.0/19`
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+struct IpRange {
+    network: IpAddr,
+    prefix: u32,
+}
+
+impl IpRange {
+    /// Parses `address/prefix`, or a bare address as a single host
+    fn parse(range: &str) -> Option<IpRange> {
+        let (address, prefix) = match range.trim().split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix.parse().ok()?)),
+            None => (range.trim(), None),
+        };
+        let network: IpAddr = address.parse().ok()?;
+        let bits = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(bits);
+        (prefix <= bits).then_some(IpRange { network, prefix })
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        let mask = |bits: u32| u128::MAX.checked_shl(bits - self.prefix).unwrap_or(0);
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = mask(32) as u32;
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = mask(128);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+const GOOGLE_RANGES: &[&str] = &["66.249.64.0/19", "2001:4860:4801::/48"];
+
+#[rustfmt::skip]
+const BING_RANGES: &[&str] = &[
+    "157.55.39.0/24", "207.46.13.0/24", "40.77.167.0/24", "13.66.139.0/24",
+    "13.66.144.0/24", "52.167.144.0/24", "40.77.188.0/22", "199.30.24.0/23",
+];
+
+#[rustfmt::skip]
+const DUCKDUCKBOT_ADDRESSES: &[&str] = &[
+    "20.191.45.212", "40.88.21.235", "40.76.173.151", "40.76.163.7",
+    "20.185.79.47", "52.142.26.175", "20.185.79.15", "52.142.24.149",
+    "40.76.162.208", "40.76.163.23", "40.76.162.191", "40.76.162.247",
+];
+
+#[rustfmt::skip]
+const APPLEBOT_RANGES: &[&str] = &[
+    "17.22.237.0/24", "17.22.245.0/24", "17.22.253.0/24", "17.241.75.0/24", "17.241.219.0/24",
+    "17.241.227.0/24", "17.246.15.0/24", "17.246.19.0/24", "17.246.23.0/24",
+    "2a01:b747:3000:200::/56", "2a01:b747:3001:200::/56", "2a01:b747:3002:200::/56",
+    "2a01:b747:3003:200::/56", "2a01:b747:3004:200::/56", "2a01:b747:3005:200::/56",
+    "2a01:b747:3006:200::/56",
+];
+
+const YANDEX_DOMAINS: &[&str] = &["yandex.ru", "yandex.net", "yandex.com"];
+
+/// `(operator, IP ranges)` for operators that publish where their crawlers run
+///
+/// The ranges are a snapshot of files such as Google's `googlebot.json` and Apple's
+/// `applebot.json`. They change over time; refresh them with
+/// [`BotVerifier::ip_ranges`]. The operator is the one named in [`Bot::operator`].
+///
+/// [`Bot::operator`]: crate::Bot::operator
+const OPERATORS: &[(&str, &[&str])] = &[
+    ("Google", GOOGLE_RANGES),
+    ("Microsoft", BING_RANGES),
+    ("Apple", APPLEBOT_RANGES),
+    ("DuckDuckGo", DUCKDUCKBOT_ADDRESSES),
+];
+
+/// `(lowercase robots.txt tokens, reverse DNS domains)`
+///
+/// Only crawlers that run from infrastructure their operator publishes are listed.
+///
+/// Google's special crawlers and user-triggered fetchers resolve under `google.com`
+/// and `gae.googleusercontent.com` only: any Compute Engine VM can have a host under
+/// the rest of `googleusercontent.com`.
+#[rustfmt::skip]
+const CRAWLERS: &[(&[&str], &[&str])] = &[
+    (
+        &["googlebot", "googlebot-image", "googlebot-news", "googlebot-video", "storebot-google",
+          "google-inspectiontool", "googleother", "googleother-image", "googleother-video",
+          "google-cloudvertexbot"],
+        &["googlebot.com", "google.com"],
+    ),
+    (
+        &["adsbot-google", "adsbot-google-mobile", "mediapartners-google", "apis-google",
+          "google-safety"],
+        &["google.com"],
+    ),
+    (
+        &["feedfetcher-google", "google-site-verification", "google-notebooklm"],
+        &["google.com", "gae.googleusercontent.com"],
+    ),
+    (&["bingbot", "msnbot", "msnbot-media", "adidxbot", "bingpreview"], &["search.msn.com"]),
+    (&["applebot"], &["applebot.apple.com"]),
+    (&["duckduckbot"], &[]),
+    (
+        &["yandexbot", "yandeximages", "yandexvideo", "yandexmobilebot", "yandexaccessibilitybot",
+          "yandexrenderresourcesbot", "yandexfavicons", "yandexdirect", "yandexnews",
+          "yandexwebmaster", "yandexmetrika"],
+        YANDEX_DOMAINS,
+    ),
+    (&["baiduspider", "baiduspider-image", "baiduspider-render"], &["baidu.com", "baidu.jp"]),
+    (&["slurp"], &["crawl.yahoo.net"]),
+    (&["petalbot"], &["petalsearch.com"]),
+    (&["seznambot"], &["seznam.cz"]),
+    (&["yeti"], &["naver.com"]),
+];
+
+/// Verifies bots against their operator's IP ranges and their reverse DNS domains
+///
+/// IP ranges are keyed by operator, such as `Google`, as operators publish them for
+/// all their crawlers at once. Domains are keyed by the crawler's robots.txt token,
+/// such as `Googlebot` or `bingbot`. [`BotVerifier::default`] starts from the
+/// embedded snapshot. Replace the records as operators publish new ones.
+///
+/// # Example
+///
+/// ```
+/// use agent_parser_ro::{BotVerifier, Resolver, UserAgentParser, Verification};
+/// use std::io;
+/// use std::net::IpAddr;
+///
+/// struct NoDns;
+///
+/// impl Resolver for NoDns {
+///     fn reverse(&self, _: IpAddr) -> io::Result<Vec<String>> {
+///         Err(io::ErrorKind::Unsupported.into())
+///     }
+///     fn forward(&self, _: &str) -> io::Result<Vec<IpAddr>> {
+///         Err(io::ErrorKind::Unsupported.into())
+///     }
+/// }
+///
+/// let verifier = BotVerifier::default()
+///     .ip_ranges("Google", ["66.249.64.0/19", "192.0.2.0/24"])
+///     .unwrap();
+/// let info = UserAgentParser::parse("Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)");
+/// let ip = "192.0.2.10".parse().unwrap();
+/// assert_eq!(verifier.verify(&info, ip, &NoDns), Verification::Verified);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BotVerifier {
+    /// IP ranges by lowercase operator
+    ranges: HashMap<String, Vec<IpRange>>,
+    /// Reverse DNS domains by lowercase robots.txt token
+    crawlers: HashMap<String, Vec<String>>,
+}
+
+impl Default for BotVerifier {
+    fn default() -> Self {
+        let ranges = OPERATORS
+            .iter()
+            .map(|(operator, ranges)| {
+                let ranges = ranges.iter().filter_map(|r| IpRange::parse(r)).collect();
+                (operator.to_ascii_lowercase(), ranges)
+            })
+            .collect();
+        let crawlers = CRAWLERS
+            .iter()
+            .flat_map(|(tokens, domains)| {
+                let domains: Vec<String> = domains.iter().map(|d| d.to_string()).collect();
+                tokens
+                    .iter()
+                    .map(move |token| (token.to_string(), domains.clone()))
+            })
+            .collect();
+        BotVerifier { ranges, crawlers }
+    }
+}
+
+impl BotVerifier {
+    /// Replaces the IP ranges of all crawlers run by `operator`, given in CIDR
+    /// notation or as single addresses
+    ///
+    /// The ranges apply to the operator's crawlers that are embedded or added with
+    /// [`BotVerifier::domains`].
+    pub fn ip_ranges<I, S>(mut self, operator: &str, ranges: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let ranges = ranges
+            .into_iter()
+            .map(|range| {
+                IpRange::parse(range.as_ref()).ok_or_else(|| Error::InvalidIpRange {
+                    range: range.as_ref().to_string(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        self.ranges.insert(operator.to_ascii_lowercase(), ranges);
+        Ok(self)
+    }
+
+    /// Replaces the domains that reverse DNS must end in for `crawler`, adding the
+    /// crawler if it is not embedded
+    ///
+    /// An added crawler is also checked against the IP ranges of the operator named
+    /// in [`Bot::operator`](crate::Bot::operator).
+    pub fn domains<I, S>(mut self, crawler: &str, domains: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let domains = domains
+            .into_iter()
+            .map(|domain| domain.as_ref().trim_matches('.').to_ascii_lowercase())
+            .collect();
+        self.crawlers.insert(crawler.to_ascii_lowercase(), domains);
+        self
+    }
+
+    /// Checks whether `ip` belongs to the operator of the bot `info` claims to be
+    ///
+    /// Bots without records, such as Lighthouse runs that any machine can start, are
+    /// [`Verification::Unverifiable`]. IPv4 addresses mapped into IPv6, such as
+    /// `::ffff:66.249.66.1`, are checked as IPv4.
+    pub fn verify(
+        &self,
+        info: &UserAgentInfo,
+        ip: IpAddr,
+        resolver: &dyn Resolver,
+    ) -> Verification {
+        let ip = ip.to_canonical();
+        let Some(bot) = &info.bot else {
+            return Verification::Unverifiable;
+        };
+        let Some(domains) = bot
+            .robots_token
+            .as_deref()
+            .and_then(|token| self.crawlers.get(&token.to_ascii_lowercase()))
+        else {
+            return Verification::Unverifiable;
+        };
+        let ranges = bot
+            .operator
+            .as_deref()
+            .and_then(|operator| self.ranges.get(&operator.to_ascii_lowercase()))
+            .map_or(&[][..], Vec::as_slice);
+
+        if ranges.iter().any(|range| range.contains(ip)) {
+            return Verification::Verified;
+        }
+        if domains.is_empty() {
+            return match ranges.is_empty() {
+                true => Verification::Unverifiable,
+                false => Verification::Spoofed,
+            };
+        }
+
+        let Ok(hosts) = resolver.reverse(ip) else {
+            return Verification::Unverifiable;
+        };
+        let mut lookup_failed = false;
+        for host in hosts {
+            let host = host.trim_end_matches('.').to_ascii_lowercase();
+            let in_domain = domains.iter().any(|domain| {
+                host.strip_suffix(domain.as_str())
+                    .is_some_and(|rest| rest.ends_with('.'))
+            });
+            if !in_domain {
+                continue;
+            }
+            match resolver.forward(&host) {
+                Ok(addresses) if addresses.contains(&ip) => return Verification::Verified,
+                Ok(_) => {}
+                Err(_) => lookup_failed = true,
+            }
+        }
+        match lookup_failed {
+            true => Verification::Unverifiable,
+            false => Verification::Spoofed,
+        }
+    }
+}
+
+/// Checks whether `ip` belongs to the operator of the bot `info` claims to be, using
+/// the embedded IP ranges and `resolver` for forward-confirmed reverse DNS
+///
+/// See [`BotVerifier`] to use updated IP ranges.
+pub fn verify_bot(info: &UserAgentInfo, ip: IpAddr, resolver: &dyn Resolver) -> Verification {
+    lazy_static! {
+        static ref DEFAULT: BotVerifier = BotVerifier::default();
+    }
+    DEFAULT.verify(info, ip, resolver)
+}
@@ -0,0 +1,54 @@
+//! Version numbers extracted from user agent tokens.
+
+use serde::{Deserialize, Serialize};
+
+/// A dotted version number such as `120.0.6099.119`.
+///
+/// The first four numeric components are exposed as `major`, `minor`, `patch`
+/// and `build`; `raw` keeps the version as it appeared in the user agent, except
+/// that OS versions written with underscores, as iOS and macOS do, use dots.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct Version {
+    pub major: u32,
+    pub minor: Option<u32>,
+    pub patch: Option<u32>,
+    pub build: Option<u32>,
+    pub raw: String,
+}
+
+impl Version {
+    /// Parses a version string whose components are separated by `.` or `_`
+    ///
+    /// Parsing stops at the first character that is not a digit or a separator, so
+    /// `"9.7.2.51367AP"` yields `9.7.2.51367`. Returns `None` when the string does not
+    /// start with a digit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use agent_parser_ro::Version;
+    ///
+    /// let version = Version::parse("16_6").unwrap();
+    /// assert_eq!(version.major, 16);
+    /// assert_eq!(version.minor, Some(6));
+    /// ```
+    pub fn parse(s: &str) -> Option<Version> {
+        let end = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '_'))
+            .unwrap_or(s.len());
+        let raw = s[..end].trim_end_matches(['.', '_']);
+        if !raw.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+
+        let mut parts = raw.split(['.', '_']).map(|p| p.parse::<u32>().ok());
+        let major = parts.next().flatten()?;
+        Some(Version {
+            major,
+            minor: parts.next().flatten(),
+            patch: parts.next().flatten(),
+            build: parts.next().flatten(),
+            raw: raw.to_string(),
+        })
+    }
+}
@@ -1,4 +1,12 @@
-use agent_parser_ro::{Browser, DeviceType, OperatingSystem, UserAgentParser};
+use agent_parser_ro::{
+    App, AppleDeviceClass, AppleModel, Architecture, Automation, BotCategory, BotVerifier, Browser,
+    ClientHints, ClientKind, Confidence, DeviceType, Embedding, Engine, Error, Field,
+    OperatingSystem, Outcome, Resolver, RobotsTxt, Rule, Source, Token, UserAgentData,
+    UserAgentParser, Verification, tokenize, verify_bot,
+};
+use std::collections::HashMap;
+use std::io;
+use std::net::IpAddr;
 
     fn assert_ua(
         ua: &str,
@@ -299,3 +307,1616 @@ use agent_parser_ro::{Browser, DeviceType, OperatingSystem, UserAgentParser};
             DeviceType::Mobile,
         );
     }
+
+    // Browser Versions
+    #[test]
+    fn test_browser_versions() {
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (iPhone; CPU iPhone OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) CriOS/120.0.6099.119 Mobile/15E148 Safari/604.1",
+        );
+        let version = info.browser_version.unwrap();
+        assert_eq!(version.major, 120);
+        assert_eq!(version.minor, Some(0));
+        assert_eq!(version.patch, Some(6099));
+        assert_eq!(version.build, Some(119));
+        assert_eq!(version.raw, "120.0.6099.119");
+
+        // Safari reports its version in `Version/`
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (iPad; CPU OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.6 Mobile/15E148 Safari/604.1",
+        );
+        assert_eq!(info.browser_version.unwrap().raw, "16.6");
+
+        // The `Safari/` product carries a WebKit build, not a Safari version
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Macintosh; U; PPC Mac OS X; en) AppleWebKit/418.8 (KHTML, like Gecko) Safari/419.3",
+        );
+        assert_eq!(info.browser, Browser::Safari);
+        assert_eq!(info.browser_version, None);
+
+        // IE11 only reports `rv:`
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Windows NT 6.1; WOW64; Trident/7.0; AS; rv:11.0) like Gecko",
+        );
+        assert_eq!(info.browser_version.unwrap().major, 11);
+
+        // Opera Mini
+        let info = UserAgentParser::parse(
+            "Opera/9.80 (Android; Opera Mini/8.0.1807/36.1609; U; en) Presto/2.12.423 Version/12.16",
+        );
+        assert_eq!(info.browser_version.unwrap().raw, "8.0.1807");
+
+        // Chromium-based Opera
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 OPR/106.0.0.0",
+        );
+        assert_eq!(info.browser, Browser::Opera);
+        assert_eq!(info.browser_version.unwrap().major, 106);
+
+        // Edge on Xbox
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; Xbox; Xbox One) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edge/44.18363.8131",
+        );
+        assert_eq!(info.browser_version.unwrap().raw, "44.18363.8131");
+
+        // Bots without a version
+        let info = UserAgentParser::parse("This is not a real user agent");
+        assert_eq!(info.browser_version, None);
+    }
+
+    // Operating System Versions
+    #[test]
+    fn test_os_versions() {
+        fn os_version(ua: &str) -> String {
+            UserAgentParser::parse(ua).os_version.unwrap().raw
+        }
+
+        assert_eq!(
+            os_version("Mozilla/5.0 (Windows NT 6.1; WOW64; Trident/7.0; AS; rv:11.0) like Gecko"),
+            "6.1"
+        );
+
+        // Windows versions are NT versions, with the release named separately
+        let windows = |nt: &str| {
+            let ua = format!("Mozilla/5.0 (Windows NT {}; rv:52.0) Gecko/20100101 Firefox/52", nt);
+            let info = UserAgentParser::parse(&ua);
+            let version = info.os_version.unwrap();
+            (version.major, version.minor, version.raw, info.os_release)
+        };
+        let release = |major, minor, nt: &str, release: &str| {
+            (major, Some(minor), nt.to_string(), Some(release.to_string()))
+        };
+        assert_eq!(windows("5.0"), release(5, 0, "5.0", "2000"));
+        assert_eq!(windows("5.1"), release(5, 1, "5.1", "XP"));
+        assert_eq!(windows("5.2"), release(5, 2, "5.2", "XP"));
+        assert_eq!(windows("6.0"), release(6, 0, "6.0", "Vista"));
+        assert_eq!(windows("6.1"), release(6, 1, "6.1", "7"));
+        assert_eq!(windows("6.2"), release(6, 2, "6.2", "8"));
+        assert_eq!(windows("6.3"), release(6, 3, "6.3", "8.1"));
+        assert_eq!(windows("10.0"), release(10, 0, "10.0", "10"));
+        assert_eq!(windows("6.4").3, None);
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (iPhone; CPU iPhone OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.6 Mobile/15E148 Safari/604.1",
+        );
+        assert_eq!(info.os_release, None);
+        assert_eq!(
+            os_version("Mozilla/5.0 (iPhone; CPU iPhone OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.6 Mobile/15E148 Safari/604.1"),
+            "16.6"
+        );
+        assert_eq!(
+            os_version("Mozilla/5.0 (iPad; CPU OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.6 Mobile/15E148 Safari/604.1"),
+            "16.6"
+        );
+        assert_eq!(
+            os_version("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.1 Safari/605.1.15"),
+            "10.15.7"
+        );
+        assert_eq!(
+            os_version("Mozilla/5.0 (Linux; Android 13; SM-A536B) AppleWebKit/537.36 (KHTML, like Gecko) SamsungBrowser/21.0 Chrome/110.0.5481.154 Mobile Safari/537.36"),
+            "13"
+        );
+        assert_eq!(
+            os_version("Mozilla/5.0 (X11; CrOS x86_64 14541.0.0) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36"),
+            "14541.0.0"
+        );
+        assert_eq!(
+            os_version("Mozilla/5.0 (SMART-TV; Linux; Tizen 6.5) AppleWebKit/537.36 (KHTML, like Gecko) SamsungBrowser/5.2 Chrome/92.0.4515.166 TV Safari/537.36"),
+            "6.5"
+        );
+        assert_eq!(
+            os_version("Mozilla/5.0 (PlayStation 5 8.00) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/15.4 Safari/605.1.15"),
+            "8.00"
+        );
+
+        let version = UserAgentParser::parse(
+            "Mozilla/5.0 (iPhone; CPU iPhone OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.6 Mobile/15E148 Safari/604.1",
+        )
+        .os_version
+        .unwrap();
+        assert_eq!((version.major, version.minor), (16, Some(6)));
+
+        assert_eq!(
+            UserAgentParser::parse("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36").os_version,
+            None
+        );
+    }
+
+    // Rendering Engines
+    #[test]
+    fn test_engines() {
+        fn engine(ua: &str) -> (Engine, Option<String>) {
+            let info = UserAgentParser::parse(ua);
+            (info.engine, info.engine_version.map(|v| v.raw))
+        }
+
+        assert_eq!(
+            engine("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36"),
+            (Engine::Blink, Some("120.0.0.0".to_string()))
+        );
+        assert_eq!(
+            engine("Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:109.0) Gecko/20100101 Firefox/115.0"),
+            (Engine::Gecko, Some("109.0".to_string()))
+        );
+        assert_eq!(
+            engine("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.1 Safari/605.1.15"),
+            (Engine::WebKit, Some("605.1.15".to_string()))
+        );
+
+        // Chrome and Firefox on iOS are WebKit
+        assert_eq!(
+            engine("Mozilla/5.0 (iPhone; CPU iPhone OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) CriOS/120.0.6099.119 Mobile/15E148 Safari/604.1").0,
+            Engine::WebKit
+        );
+        assert_eq!(
+            engine("Mozilla/5.0 (iPhone; CPU iPhone OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) FxiOS/115.0 Mobile/15E148 Safari/605.1.15").0,
+            Engine::WebKit
+        );
+
+        assert_eq!(
+            engine("Opera/9.80 (Android; Opera Mini/8.0.1807/36.1609; U; en) Presto/2.12.423 Version/12.16"),
+            (Engine::Presto, Some("2.12.423".to_string()))
+        );
+        assert_eq!(
+            engine("Mozilla/5.0 (Windows NT 10.0; Win64; x64; Xbox; Xbox One) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edge/44.18363.8131"),
+            (Engine::EdgeHTML, Some("44.18363.8131".to_string()))
+        );
+        assert_eq!(
+            engine("Mozilla/5.0 (Windows NT 6.1; WOW64; Trident/7.0; AS; rv:11.0) like Gecko"),
+            (Engine::Trident, Some("7.0".to_string()))
+        );
+        assert_eq!(engine("This is not a real user agent"), (Engine::Unknown, None));
+    }
+
+    // CPU Architecture
+    #[test]
+    fn test_architecture() {
+        fn arch(ua: &str) -> (Architecture, Option<bool>) {
+            let info = UserAgentParser::parse(ua);
+            (info.architecture, info.is_64bit)
+        }
+
+        assert_eq!(
+            arch("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36"),
+            (Architecture::X86_64, Some(true))
+        );
+        assert_eq!(
+            arch("Mozilla/5.0 (Windows NT 6.1; WOW64; Trident/7.0; AS; rv:11.0) like Gecko"),
+            (Architecture::X86_64, Some(true))
+        );
+        assert_eq!(
+            arch("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36"),
+            (Architecture::X86_64, Some(true))
+        );
+        assert_eq!(
+            arch("Mozilla/5.0 (X11; Linux i686; rv:109.0) Gecko/20100101 Firefox/115.0"),
+            (Architecture::X86, Some(false))
+        );
+        assert_eq!(
+            arch("Mozilla/5.0 (X11; Linux armv7l) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/88.0.4324.182 Safari/537.36 CrKey/1.54.250320"),
+            (Architecture::Arm, Some(false))
+        );
+        assert_eq!(
+            arch("Mozilla/5.0 (X11; Linux aarch64; rv:109.0) Gecko/20100101 Firefox/115.0"),
+            (Architecture::Arm64, Some(true))
+        );
+        assert_eq!(
+            arch("Mozilla/5.0 (Linux; Android 13; SM-A536B) AppleWebKit/537.36 (KHTML, like Gecko) SamsungBrowser/21.0 Chrome/110.0.5481.154 Mobile Safari/537.36"),
+            (Architecture::Unknown, None)
+        );
+    }
+
+    // Device Brand and Model
+    #[test]
+    fn test_device_models() {
+        fn device(ua: &str) -> (Option<String>, Option<String>, Option<String>) {
+            let device = UserAgentParser::parse(ua).device;
+            (device.brand, device.model, device.model_code)
+        }
+        fn some(s: &str) -> Option<String> {
+            Some(s.to_string())
+        }
+
+        let samsung = device("Mozilla/5.0 (Linux; Android 13; SM-A536B) AppleWebKit/537.36 (KHTML, like Gecko) SamsungBrowser/21.0 Chrome/110.0.5481.154 Mobile Safari/537.36");
+        assert_eq!(samsung.0, some("Samsung"));
+        assert_eq!(samsung.2, some("SM-A536B"));
+        assert_eq!(
+            device("Mozilla/5.0 (Linux; U; Android 10; en-US; RMX2061 Build/QKQ1.200428.002) AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 UCBrowser/13.0.0.1308 Mobile Safari/537.36").2,
+            some("RMX2061")
+        );
+        assert_eq!(
+            device("Mozilla/5.0 (Linux; Android 10; Quest 2) AppleWebKit/537.36 (KHTML, like Gecko) OculusBrowser/20.0.0.6.74.348674094 SamsungBrowser/4.0 Chrome/99.0.4844.88 Mobile VR Safari/537.36"),
+            (some("Meta"), some("Quest 2"), some("Quest 2"))
+        );
+        assert_eq!(
+            device("Mozilla/5.0 (Linux; Android 9; KFMAWI) AppleWebKit/537.36 (KHTML, like Gecko) Silk/86.3.13 like Chrome/86.0.4240.198 Safari/537.36").0,
+            some("Amazon")
+        );
+        assert_eq!(
+            device("Mozilla/5.0 (iPhone; CPU iPhone OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.6 Mobile/15E148 Safari/604.1"),
+            (some("Apple"), some("iPhone"), None)
+        );
+        assert_eq!(
+            device("Mozilla/5.0 (PlayStation 5 8.00) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/15.4 Safari/605.1.15"),
+            (some("Sony"), some("PlayStation 5"), None)
+        );
+        assert_eq!(
+            device("Mozilla/5.0 (Nintendo Switch; WifiWebAuthApplet) AppleWebKit/609.4 (KHTML, like Gecko) NF/6.0.2.20.2 NintendoBrowser/5.1.0.22401").1,
+            some("Nintendo Switch")
+        );
+
+        // The most specific console name wins
+        assert_eq!(
+            device("Mozilla/5.0 (Windows NT 10.0; Win64; x64; Xbox; Xbox One) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edge/44.18363.8131"),
+            (some("Microsoft"), some("Xbox One"), None)
+        );
+        assert_eq!(
+            device("Mozilla/5.0 (Windows NT 10.0; Win64; x64; Xbox; Xbox Series X) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.0.0").1,
+            some("Xbox Series X")
+        );
+
+        // Nested parentheses belong to the model
+        assert_eq!(
+            device("Mozilla/5.0 (Linux; Android 12; moto g power (2022)) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36"),
+            (some("Motorola"), some("moto g power (2022)"), some("moto g power (2022)"))
+        );
+
+        // Vendor entries are not models
+        assert_eq!(
+            device("Mozilla/5.0 (Windows Phone 10.0; Android 6.0.1; Microsoft; Lumia 950) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/52.0.2743.116 Mobile Safari/537.36 Edge/15.15063"),
+            (some("Microsoft"), some("Lumia 950"), some("Lumia 950"))
+        );
+
+        // Android without a model in the comment
+        assert_eq!(
+            device("Mozilla/5.0 (Android 13; Mobile; rv:109.0) Gecko/109.0 Firefox/115.0"),
+            (None, None, None)
+        );
+    }
+
+    // Android Model Database
+    #[cfg(feature = "android-models")]
+    #[test]
+    fn test_android_model_database() {
+        fn device(ua: &str) -> (Option<String>, Option<String>) {
+            let device = UserAgentParser::parse(ua).device;
+            (device.brand, device.model)
+        }
+        fn some(s: &str) -> Option<String> {
+            Some(s.to_string())
+        }
+
+        assert_eq!(
+            device("Mozilla/5.0 (Linux; Android 13; SM-A536B) AppleWebKit/537.36 (KHTML, like Gecko) SamsungBrowser/21.0 Chrome/110.0.5481.154 Mobile Safari/537.36"),
+            (some("Samsung"), some("Galaxy A53 5G"))
+        );
+        assert_eq!(
+            device("Mozilla/5.0 (Linux; U; Android 10; en-US; RMX2061 Build/QKQ1.200428.002) AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 UCBrowser/13.0.0.1308 Mobile Safari/537.36"),
+            (some("Realme"), some("Realme 6 Pro"))
+        );
+        // KFMAWI is the 2019 Fire HD 10, not a Fire HD 8
+        assert_eq!(
+            device("Mozilla/5.0 (Linux; Android 9; KFMAWI) AppleWebKit/537.36 (KHTML, like Gecko) Silk/86.3.13 like Chrome/86.0.4240.198 Safari/537.36"),
+            (some("Amazon"), some("Fire HD 10 (2019)"))
+        );
+        assert_eq!(
+            device("Mozilla/5.0 (Linux; Android 9; KFKAWI) AppleWebKit/537.36 (KHTML, like Gecko) Silk/86.3.13 like Chrome/86.0.4240.198 Safari/537.36").1,
+            some("Fire HD 8 (2018)")
+        );
+        assert_eq!(
+            device("Mozilla/5.0 (Linux; Android 13; SM-T870) AppleWebKit/537.36 (KHTML, like Gecko) SamsungBrowser/21.0 Chrome/110.0.5481.154 Safari/537.36").1,
+            some("Galaxy Tab S7")
+        );
+
+        // Unknown codes keep the vendor prefix heuristics
+        assert_eq!(
+            device("Mozilla/5.0 (Linux; Android 14; SM-Z999X) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36"),
+            (some("Samsung"), some("SM-Z999X"))
+        );
+    }
+
+    // Apple Hardware Identifiers
+    #[test]
+    fn test_apple_models() {
+        // Facebook in-app browser reports the hardware identifier in FBDV
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (iPhone; CPU iPhone OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Mobile/15E148 [FBAN/FBIOS;FBDV/iPhone14,3;FBMD/iPhone;FBSN/iOS;FBSV/16.6;FBSS/3;FBID/phone;FBLC/en_US;FBOP/5]",
+        );
+        assert_eq!(info.device.brand.as_deref(), Some("Apple"));
+        assert_eq!(info.device.model.as_deref(), Some("iPhone 13 Pro Max"));
+        assert_eq!(info.device.model_code.as_deref(), Some("iPhone14,3"));
+        assert_eq!(info.device_type, DeviceType::Mobile);
+
+        // Native app user agent
+        let info = UserAgentParser::parse("MyApp/2.4.1 (iPad13,18; iOS 17.1; Scale/2.00)");
+        assert_eq!(info.device.model.as_deref(), Some("iPad (10th generation)"));
+        assert_eq!(info.device_type, DeviceType::Tablet);
+
+        let info = UserAgentParser::parse("MyApp/2.4.1 (Watch6,18; watchOS 10.0)");
+        assert_eq!(info.device.model.as_deref(), Some("Apple Watch Ultra"));
+        assert_eq!(info.device_type, DeviceType::Smartwatch);
+
+        let model = AppleModel::lookup("RealityDevice14,1").unwrap();
+        assert_eq!(model.name, "Apple Vision Pro");
+        assert_eq!(model.class, AppleDeviceClass::Vision);
+        assert_eq!(model.class.device_type(), DeviceType::VRHeadset);
+
+        // Unreleased identifiers fall back to the class name
+        assert_eq!(AppleModel::lookup("iPhone99,1").unwrap().name, "iPhone");
+        assert_eq!(AppleModel::lookup("Pixel7,1"), None);
+        // Only full identifiers resolve
+        for partial in ["iPhone", "iPad", "iPhone14", "iPhone,3", "iPhone14,", "iPhone14,3,1"] {
+            assert_eq!(AppleModel::lookup(partial), None);
+        }
+    }
+
+    // User-Agent Client Hints
+    #[test]
+    fn test_client_hints() {
+        let ua = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+        let hints = ClientHints::from_headers([
+            ("Sec-CH-UA", r#""Not_A Brand";v="8", "Chromium";v="120", "Google Chrome";v="120""#),
+            (
+                "Sec-CH-UA-Full-Version-List",
+                r#""Not_A Brand";v="8.0.0.0", "Chromium";v="120.0.6099.130", "Google Chrome";v="120.0.6099.130""#,
+            ),
+            ("Sec-CH-UA-Platform", r#""Windows""#),
+            ("Sec-CH-UA-Platform-Version", r#""15.0.0""#),
+            ("Sec-CH-UA-Mobile", "?0"),
+            ("Sec-CH-UA-Arch", r#""arm""#),
+            ("Sec-CH-UA-Bitness", r#""64""#),
+        ]);
+
+        let brands = hints.brands();
+        assert_eq!(brands.len(), 2);
+        assert!(brands.iter().all(|brand| !brand.is_grease()));
+
+        let info = UserAgentParser::parse_with_hints(ua, &hints);
+        assert_eq!(info.browser, Browser::Chrome);
+        assert_eq!(info.browser_version.unwrap().raw, "120.0.6099.130");
+        assert_eq!(info.engine, Engine::Blink);
+        assert_eq!(info.os, OperatingSystem::Windows);
+        assert_eq!(info.os_version.unwrap().raw, "10.0");
+        assert_eq!(info.os_release.as_deref(), Some("11"));
+        assert_eq!(info.architecture, Architecture::Arm64);
+        assert_eq!(info.is_64bit, Some(true));
+        assert_eq!(info.device_type, DeviceType::Desktop);
+
+        // Windows 10 reports a platform version below 13
+        let hints = ClientHints {
+            platform: Some(r#""Windows""#.to_string()),
+            platform_version: Some(r#""10.0.0""#.to_string()),
+            ..ClientHints::default()
+        };
+        let info = UserAgentParser::parse_with_hints(ua, &hints);
+        assert_eq!(info.os_release.as_deref(), Some("10"));
+
+        // Brave only identifies itself through hints
+        let hints = ClientHints {
+            ua: Some(r#""Brave";v="120", "Chromium";v="120", "Not?A_Brand";v="24""#.to_string()),
+            ..ClientHints::default()
+        };
+        assert_eq!(UserAgentParser::parse_with_hints(ua, &hints).browser, Browser::Brave);
+
+        // The client stack and kind follow the refined fields, as with a built parser
+        let info = UserAgentParser::parse_with_hints(ua, &hints);
+        assert_eq!(info.client_stack.browser, Browser::Brave);
+        let detected = UserAgentParser::builder().build().unwrap().detect_with_hints(ua, &hints);
+        assert_eq!(
+            serde_json::to_value(&info).unwrap(),
+            serde_json::to_value(&detected).unwrap()
+        );
+        let hints = ClientHints {
+            ua: Some(r#""Google Chrome";v="120", "Chromium";v="120""#.to_string()),
+            ..ClientHints::default()
+        };
+        let info = UserAgentParser::parse_with_hints("Mozilla/5.0", &hints);
+        assert_eq!(info.browser, Browser::Chrome);
+        assert_eq!(info.client_stack.browser, Browser::Chrome);
+        assert_eq!(info.client_kind, ClientKind::Browser);
+
+        // Android model and mobile flag from hints on a reduced user agent
+        let hints = ClientHints {
+            mobile: Some("?1".to_string()),
+            model: Some(r#""SM-A536B""#.to_string()),
+            platform: Some(r#""Android""#.to_string()),
+            platform_version: Some(r#""13.0.0""#.to_string()),
+            ..ClientHints::default()
+        };
+        let info = UserAgentParser::parse_with_hints(
+            "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+            &hints,
+        );
+        assert_eq!(info.os_version.unwrap().raw, "13.0.0");
+        assert_eq!(info.device.model_code.as_deref(), Some("SM-A536B"));
+        assert_eq!(info.device.brand.as_deref(), Some("Samsung"));
+        assert_eq!(info.device_type, DeviceType::Mobile);
+
+        // Empty hints leave the user agent result untouched
+        let info = UserAgentParser::parse_with_hints(ua, &ClientHints::default());
+        assert_eq!(info.os_release.as_deref(), Some("10"));
+    }
+
+    // navigator.userAgentData Payloads
+    #[test]
+    fn test_user_agent_data() {
+        let ua = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+        let data: UserAgentData = serde_json::from_str(
+            r#"{
+                "architecture": "arm",
+                "bitness": "64",
+                "brands": [
+                    {"brand": "Not_A Brand", "version": "8"},
+                    {"brand": "Chromium", "version": "120"},
+                    {"brand": "Google Chrome", "version": "120"}
+                ],
+                "formFactors": ["Desktop"],
+                "fullVersionList": [
+                    {"brand": "Not_A Brand", "version": "8.0.0.0"},
+                    {"brand": "Chromium", "version": "120.0.6099.129"},
+                    {"brand": "Google Chrome", "version": "120.0.6099.129"}
+                ],
+                "mobile": false,
+                "model": "",
+                "platform": "macOS",
+                "platformVersion": "14.2.1",
+                "uaFullVersion": "120.0.6099.129",
+                "wow64": false
+            }"#,
+        )
+        .unwrap();
+
+        let from_ua = UserAgentParser::parse(ua);
+        assert_eq!(from_ua.os_version.unwrap().raw, "10.15.7");
+        assert_eq!(from_ua.architecture, Architecture::Unknown);
+
+        let info = UserAgentParser::parse_with_user_agent_data(ua, &data);
+        assert_eq!(info.browser, Browser::Chrome);
+        assert_eq!(info.browser_version.unwrap().raw, "120.0.6099.129");
+        assert_eq!(info.os, OperatingSystem::MacOS);
+        assert_eq!(info.os_version.unwrap().raw, "14.2.1");
+        assert_eq!(info.architecture, Architecture::Arm64);
+        assert_eq!(info.is_64bit, Some(true));
+        assert_eq!(info.device_type, DeviceType::Desktop);
+        assert_eq!(info.device.brand.as_deref(), Some("Apple"));
+
+        // Same result as the equivalent headers
+        let hints = ClientHints::from_headers([
+            (
+                "Sec-CH-UA-Full-Version-List",
+                r#""Not_A Brand";v="8.0.0.0", "Chromium";v="120.0.6099.129", "Google Chrome";v="120.0.6099.129""#,
+            ),
+            ("Sec-CH-UA-Platform", r#""macOS""#),
+            ("Sec-CH-UA-Platform-Version", r#""14.2.1""#),
+            ("Sec-CH-UA-Mobile", "?0"),
+            ("Sec-CH-UA-Model", r#""""#),
+            ("Sec-CH-UA-Arch", r#""arm""#),
+            ("Sec-CH-UA-Bitness", r#""64""#),
+            ("Sec-CH-UA-WoW64", "?0"),
+            ("Sec-CH-UA-Form-Factors", r#""Desktop""#),
+        ]);
+        let from_hints = UserAgentParser::parse_with_hints(ua, &hints);
+        assert_eq!(
+            serde_json::to_value(&from_hints).unwrap(),
+            serde_json::to_value(UserAgentParser::parse_with_user_agent_data(ua, &data)).unwrap()
+        );
+
+        // WOW64 and form factors
+        let data: UserAgentData = serde_json::from_str(
+            r#"{"platform": "Windows", "architecture": "x86", "bitness": "32", "wow64": true, "formFactors": ["XR"]}"#,
+        )
+        .unwrap();
+        let info = UserAgentParser::parse_with_user_agent_data(
+            "Mozilla/5.0 (Windows NT 10.0) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+            &data,
+        );
+        assert_eq!(info.architecture, Architecture::X86_64);
+        assert_eq!(info.device_type, DeviceType::VRHeadset);
+    }
+
+    // Reduced User-Agent Placeholders
+    #[test]
+    fn test_frozen_fields() {
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        );
+        assert!(info.frozen.os_version);
+        assert_eq!(info.frozen.os_version_at_least.unwrap().raw, "10.15");
+        assert!(info.frozen.browser_version);
+        assert!(info.frozen.engine_version);
+        assert!(!info.frozen.device_model);
+
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+        );
+        assert!(info.frozen.os_version);
+        assert_eq!(info.frozen.os_version_at_least, None);
+        assert!(info.frozen.device_model);
+        // Android Chrome's version is read from `Chrome/`, whatever the browser field says
+        assert!(info.frozen.browser_version);
+        assert!(info.frozen.engine_version);
+
+        // A browser with its own version is not frozen by the Chrome token it carries
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) SamsungBrowser/23.0 Chrome/115.0.0.0 Mobile Safari/537.36",
+        );
+        assert!(!info.frozen.browser_version);
+        assert!(info.frozen.engine_version);
+
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        );
+        assert!(info.frozen.os_version);
+        assert_eq!(info.frozen.os_version_at_least.unwrap().raw, "10.0");
+
+        // Real data is not flagged
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Linux; Android 13; SM-A536B) AppleWebKit/537.36 (KHTML, like Gecko) SamsungBrowser/21.0 Chrome/110.0.5481.154 Mobile Safari/537.36",
+        );
+        assert!(!info.frozen.any());
+
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Windows NT 6.1; WOW64; Trident/7.0; AS; rv:11.0) like Gecko",
+        );
+        assert!(!info.frozen.any());
+
+        // Client hints replace the placeholders with real data
+        let hints = ClientHints::from_headers([
+            ("Sec-CH-UA-Full-Version-List", r#""Chromium";v="120.0.6099.129", "Google Chrome";v="120.0.6099.129""#),
+            ("Sec-CH-UA-Platform", r#""Android""#),
+            ("Sec-CH-UA-Platform-Version", r#""14.0.0""#),
+            ("Sec-CH-UA-Model", r#""Pixel 8""#),
+        ]);
+        let info = UserAgentParser::parse_with_hints(
+            "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+            &hints,
+        );
+        assert!(!info.frozen.any());
+        assert_eq!(info.os_version.unwrap().major, 14);
+        assert_eq!(info.device.brand.as_deref(), Some("Google"));
+    }
+
+    // Custom Rules
+    #[test]
+    fn test_custom_rules() {
+        let parser = UserAgentParser::builder()
+            .rule(
+                Rule::new(r"AcmeApp/(?P<version>[\d.]+)")
+                    .browser(Browser::Other("AcmeApp".to_string()))
+                    .priority(10),
+            )
+            .rule(Rule::new(r"\bAcmeOS (?P<os_version>[\d.]+)").os(OperatingSystem::Other("AcmeOS".to_string())))
+            .rule(Rule::new(r"\bSM-A536B\b").device_type(DeviceType::Tablet))
+            .rule(Rule::new(r"\bDTV\b").os(OperatingSystem::Linux).priority(-1))
+            .build()
+            .unwrap();
+
+        // Positive priority overrides the built-in detection
+        let info = parser.detect(
+            "Mozilla/5.0 (Linux; Android 13; SM-A536B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/110.0.5481.154 Mobile Safari/537.36 AcmeApp/3.2.1",
+        );
+        assert_eq!(info.browser, Browser::Other("AcmeApp".to_string()));
+        assert_eq!(info.browser_version.unwrap().raw, "3.2.1");
+        // Priority 0 only fills unknown fields
+        assert_eq!(info.device_type, DeviceType::Mobile);
+        assert_eq!(info.os, OperatingSystem::Android);
+
+        let info = parser.detect("Mozilla/5.0 (AcmeOS 2.1) acmeapp/1.0");
+        assert_eq!(info.os, OperatingSystem::Other("AcmeOS".to_string()));
+        assert_eq!(info.os_version.unwrap().raw, "2.1");
+        assert_eq!(info.browser_version.unwrap().raw, "1.0");
+
+        let info = parser.detect(
+            "Mozilla/5.0 (DTV) AppleWebKit/531.2 (KHTML, like Gecko) NX/3.0.0.9.12 (PhilipsTV; 65OLED706/12; TPM211CE_R.101.002.178.222;) Capella/1.0 WebKit/531.2",
+        );
+        assert_eq!(info.os, OperatingSystem::Linux);
+
+        // Without custom rules an instance matches the associated functions
+        let ua = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+        let default = UserAgentParser::default().detect(ua);
+        assert_eq!(default.browser, UserAgentParser::parse(ua).browser);
+        assert_eq!(parser.detect(ua).browser, Browser::Chrome);
+
+        // Extreme priorities sort without overflowing
+        let parser = UserAgentParser::builder()
+            .rule(Rule::new(r"\bAcme\b").browser(Browser::Other("Lowest".to_string())).priority(i32::MIN))
+            .rule(Rule::new(r"\bAcme\b").browser(Browser::Other("Highest".to_string())).priority(i32::MAX))
+            .build()
+            .unwrap();
+        assert_eq!(parser.detect("Acme/1.0").browser, Browser::Other("Highest".to_string()));
+
+        // Invalid and oversized patterns are rejected
+        assert!(matches!(
+            UserAgentParser::builder().rule(Rule::new("(unclosed")).build(),
+            Err(Error::InvalidPattern { .. })
+        ));
+        assert!(matches!(
+            UserAgentParser::builder()
+                .max_pattern_length(8)
+                .rule(Rule::new("a much longer pattern"))
+                .build(),
+            Err(Error::PatternTooLong { limit: 8, .. })
+        ));
+        assert!(matches!(
+            UserAgentParser::builder()
+                .size_limit(1024)
+                .rule(Rule::new(r"\w{1000}"))
+                .build(),
+            Err(Error::InvalidPattern { .. })
+        ));
+    }
+
+    // uap-core Rule Files
+    #[cfg(feature = "uap")]
+    #[test]
+    fn test_uap_rules() {
+        use agent_parser_ro::UapRules;
+
+        let yaml = r#"
+user_agent_parsers:
+  - regex: '(AcmeBrowser)/(\d+)\.(\d+)'
+  - regex: '(Chrome)/(\d+)\.(\d+)'
+    family_replacement: 'Chrome'
+os_parsers:
+  - regex: '(AcmeOS) (\d+)_(\d+)'
+    os_replacement: 'AcmeOS'
+  - regex: '(CPU OS) (\d+)_(\d+)'
+    os_replacement: 'iOS'
+  - regex: '(Windows NT 6\.1)'
+    os_replacement: 'Windows'
+    os_v1_replacement: '7'
+device_parsers:
+  - regex: '(acmebot)'
+    regex_flag: 'i'
+    device_replacement: 'Spider'
+    brand_replacement: 'Spider'
+    model_replacement: 'Desktop'
+  - regex: '; (ACME)-(\w+)\)'
+    device_replacement: '$1 $2'
+    brand_replacement: 'Acme'
+    model_replacement: '$2'
+  - regex: '(AcmePad)'
+    device_replacement: 'Generic Tablet'
+"#;
+        let rules = UapRules::from_yaml(yaml).unwrap();
+        let parser = UserAgentParser::builder().uap_rules(rules.clone()).build().unwrap();
+
+        let info = parser.detect("Mozilla/5.0 (AcmeOS 3_1; ACME-X200) AcmeBrowser/7.4");
+        assert_eq!(info.browser, Browser::Other("AcmeBrowser".to_string()));
+        assert_eq!(info.browser_version.unwrap().raw, "7.4");
+        assert!(matches!(
+            info.evidence.browser.unwrap().rule,
+            Source::UapRule { regex } if regex.starts_with("(AcmeBrowser)")
+        ));
+        assert_eq!(info.os, OperatingSystem::Other("AcmeOS".to_string()));
+        assert_eq!(info.os_version.unwrap().raw, "3.1");
+        assert_eq!(info.device.brand.as_deref(), Some("Acme"));
+        assert_eq!(info.device.model.as_deref(), Some("X200"));
+        // A family without a known form factor is kept rather than dropped
+        assert_eq!(info.device_type, DeviceType::Other("ACME X200".to_string()));
+
+        let info = parser.detect("Mozilla/5.0 (compatible; AcmeBot/1.0)");
+        assert_eq!(info.device_type, DeviceType::Bot);
+
+        // Generic families set the form factor, brand and model families do not
+        let info = parser.detect("Mozilla/5.0 (AcmeOS 3_1; AcmePad) AcmeBrowser/7.4");
+        assert_eq!(info.device_type, DeviceType::Tablet);
+        let info = parser.detect("Mozilla/5.0 (Linux; Android 13; ACME-X200) AcmeBrowser/7.4");
+        assert_eq!(info.device_type, DeviceType::Mobile);
+
+        // iPadOS is kept when uap-core reports iOS
+        let info = parser.detect(
+            "Mozilla/5.0 (iPad; CPU OS 17_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.2 Mobile/15E148 Safari/604.1",
+        );
+        assert_eq!(info.os, OperatingSystem::IPadOS);
+        assert_eq!(info.os_version.unwrap().raw, "17.2");
+
+        // uap-core names Windows releases, the version stays the NT one
+        let info =
+            parser.detect("Mozilla/5.0 (Windows NT 6.1; rv:52.0) Gecko/20100101 Firefox/52.0");
+        assert_eq!(info.os_version.unwrap().raw, "6.1");
+        assert_eq!(info.os_release.as_deref(), Some("7"));
+
+        // Custom rules still take precedence over rule files
+        let parser = UserAgentParser::builder()
+            .uap_rules(rules.clone())
+            .rule(Rule::new("AcmeBrowser").browser(Browser::Opera).priority(1))
+            .build()
+            .unwrap();
+        let info = parser.detect("Mozilla/5.0 (AcmeOS 3_1) AcmeBrowser/7.4");
+        assert_eq!(info.browser, Browser::Opera);
+
+        let path = std::env::temp_dir().join("agent-parser-ro-regexes.yaml");
+        std::fs::write(&path, yaml).unwrap();
+        assert_eq!(UapRules::from_path(&path).unwrap(), rules);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            UapRules::from_path("/nonexistent/regexes.yaml"),
+            Err(Error::Io(_))
+        ));
+        assert!(matches!(
+            UapRules::from_yaml("user_agent_parsers: 3"),
+            Err(Error::Yaml(_))
+        ));
+        let invalid = UapRules::from_yaml("os_parsers:\n  - regex: '(unclosed'\n").unwrap();
+        assert!(matches!(
+            UserAgentParser::builder().uap_rules(invalid).build(),
+            Err(Error::InvalidPattern { .. })
+        ));
+    }
+
+    #[cfg(feature = "uap")]
+    #[test]
+    fn test_uap_core_excerpt() {
+        use agent_parser_ro::UapRules;
+
+        // Verbatim uap-core rules compile within the default limits
+        let rules = UapRules::from_yaml(include_str!("fixtures/regexes.yaml")).unwrap();
+        let parser = UserAgentParser::builder().uap_rules(rules).build().unwrap();
+
+        let info = parser.detect(
+            "Mozilla/5.0 (Linux; Android 13; Lenovo TB-X606F Build/TP1A) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        );
+        assert_eq!(info.browser, Browser::Chrome);
+        assert_eq!(info.os, OperatingSystem::Android);
+        assert_eq!(info.device.brand.as_deref(), Some("Lenovo"));
+        assert_eq!(info.device.model.as_deref(), Some("TB-X606F"));
+
+        let info = parser.detect("Mozilla/5.0 (Series40; Nokia_X2-02/10.90) Profile/MIDP-2.1");
+        assert_eq!(info.device.brand.as_deref(), Some("Nokia"));
+
+        let info = parser.detect(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.2210.91",
+        );
+        assert_eq!(info.browser, Browser::Edge);
+        assert_eq!(info.os_release.as_deref(), Some("10"));
+    }
+
+    // Token-Aware Matching
+    #[test]
+    fn test_token_boundaries() {
+        // "tor" inside Motorola is not Tor Browser
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Linux; Android 10; motorola one action) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+        );
+        assert_ne!(info.browser, Browser::Tor);
+        assert_eq!(info.device_type, DeviceType::Mobile);
+
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Director/3.1 Chrome/120.0.0.0 Safari/537.36",
+        );
+        assert_eq!(info.browser, Browser::Chrome);
+
+        // "tv" and "watch" must be whole words inside a comment
+        assert_ua(
+            "Mozilla/5.0 (Linux; Android 13; Pixel 7; Activ8TVGuide) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+            OperatingSystem::Android,
+            Browser::Safari,
+            DeviceType::Mobile,
+        );
+        assert_ua(
+            "WatchESPN/5.2 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Mobile/15E148",
+            OperatingSystem::IOS,
+            Browser::Unknown,
+            DeviceType::Mobile,
+        );
+
+        // "wv" inside a model code is not a WebView
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Linux; Android 12; LG-WVX500) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+        );
+        assert_ne!(info.browser, Browser::WebView);
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Linux; Android 12; Pixel 6; wv) AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 Chrome/120.0.0.0 Mobile Safari/537.36",
+        );
+        assert_eq!(info.browser, Browser::WebView);
+
+        // Longer product names are listed on their own
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Linux; U; Android 4.4.2; zh-cn; SM-G900F Build/KOT49H) AppleWebKit/534.30 (KHTML, like Gecko) Version/4.0 DolphinBrowserCN/11.4.2 Mobile Safari/534.30",
+        );
+        assert_eq!(info.browser, Browser::Dolphin);
+        assert_eq!(info.browser_version.unwrap().raw, "11.4.2");
+
+        // Model code prefixes still match when followed by digits
+        assert_ua(
+            "Mozilla/5.0 (Linux; Android 13; SM-T970) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+            OperatingSystem::Android,
+            Browser::Chrome,
+            DeviceType::Tablet,
+        );
+    }
+
+    // Tokenizer
+    #[test]
+    fn test_tokenize() {
+        let ua = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) myapp/1.0.0 Chrome/120.0.6099.109 Electron/28.1.0 Safari/537.36";
+        let tokens = tokenize(ua);
+        let products: Vec<_> = tokens
+            .iter()
+            .filter_map(|token| match token {
+                Token::Product(product) => Some((product.name, product.version)),
+                Token::Comment(_) => None,
+            })
+            .collect();
+        assert_eq!(
+            products,
+            [
+                ("Mozilla", Some("5.0")),
+                ("AppleWebKit", Some("537.36")),
+                ("myapp", Some("1.0.0")),
+                ("Chrome", Some("120.0.6099.109")),
+                ("Electron", Some("28.1.0")),
+                ("Safari", Some("537.36")),
+            ]
+        );
+
+        let Token::Comment(platform) = &tokens[1] else {
+            panic!("expected a comment, got {:?}", tokens[1]);
+        };
+        assert_eq!(platform.text, "Windows NT 10.0; Win64; x64");
+        assert_eq!(&ua[platform.span.clone()], "(Windows NT 10.0; Win64; x64)");
+        let entries: Vec<_> = platform.entries().collect();
+        assert_eq!(entries[0], ("Windows NT 10.0", 13..28));
+        assert_eq!(entries[2].0, "x64");
+        assert_eq!(&ua[entries[2].1.clone()], "x64");
+        for token in &tokens {
+            assert!(!ua[token.span()].starts_with(' '));
+        }
+
+        // Nested and escaped comments, products without versions, unclosed comments
+        let ua = r"Foo (a; (b; c); d\); e) Bar Baz/2 (open; end";
+        let tokens = tokenize(ua);
+        assert_eq!(tokens.len(), 5);
+        let Token::Comment(nested) = &tokens[1] else {
+            panic!("expected a comment, got {:?}", tokens[1]);
+        };
+        let entries: Vec<_> = nested.entries().map(|(entry, _)| entry).collect();
+        assert_eq!(entries, ["a", "(b; c)", r"d\)", "e"]);
+        assert!(matches!(
+            &tokens[2],
+            Token::Product(product) if product.name == "Bar" && product.version.is_none()
+        ));
+        let Token::Comment(open) = &tokens[4] else {
+            panic!("expected a comment, got {:?}", tokens[4]);
+        };
+        assert_eq!(open.text, "open; end");
+        assert_eq!(open.span.end, ua.len());
+
+        assert!(tokenize("").is_empty());
+    }
+
+    // Detection Evidence
+    #[test]
+    fn test_evidence() {
+        let ua = "Mozilla/5.0 (Linux; Android 13; SM-A536B) AppleWebKit/537.36 (KHTML, like Gecko) SamsungBrowser/21.0 Chrome/110.0.5481.154 Mobile Safari/537.36";
+        let info = UserAgentParser::parse(ua);
+        let browser = info.evidence.browser.as_ref().unwrap();
+        assert_eq!(browser.token, "SamsungBrowser");
+        assert_eq!(&ua[browser.span.clone().unwrap()], "SamsungBrowser");
+        assert_eq!(
+            browser.rule,
+            Source::Keyword {
+                keyword: "samsungbrowser".to_string(),
+                tier: 0
+            }
+        );
+        assert_eq!(browser.confidence, Confidence::High);
+        assert_eq!(info.evidence.os.as_ref().unwrap().token, "Android");
+        assert_eq!(info.evidence.device_type.as_ref().unwrap().token, "Android");
+        assert_eq!(info.evidence.confidence(), Confidence::Medium);
+
+        // The desktop fallback ranks below any token match
+        let info = UserAgentParser::parse("Mozilla/5.0 (Windows NT 10.0; Win64; x64) Firefox/121.0");
+        let device = info.evidence.device_type.unwrap();
+        assert_eq!(device.rule, Source::DesktopFallback);
+        assert_eq!(device.token, "Windows");
+        assert_eq!(device.confidence, Confidence::Low);
+        assert!(device.confidence < info.evidence.browser.unwrap().confidence);
+
+        let info = UserAgentParser::parse("MyApp/2.4.1 (iPad13,18; iOS 17.1; Scale/2.00)");
+        let device = info.evidence.device_type.unwrap();
+        assert_eq!(device.rule, Source::AppleModel);
+        assert_eq!(device.token, "iPad13,18");
+
+        // Undetected fields have no evidence and count as low confidence
+        let info = UserAgentParser::parse("");
+        assert!(info.evidence.os.is_none());
+        assert_eq!(info.evidence.confidence(), Confidence::Low);
+
+        // Client hints and custom rules record themselves
+        let hints = ClientHints {
+            ua: Some(r#""Microsoft Edge";v="120", "Chromium";v="120""#.to_string()),
+            platform: Some(r#""Windows""#.to_string()),
+            ..Default::default()
+        };
+        let info = UserAgentParser::parse_with_hints(ua, &hints);
+        let browser = info.evidence.browser.unwrap();
+        assert_eq!(browser.rule, Source::ClientHints);
+        assert_eq!(browser.token, "Microsoft Edge");
+        assert_eq!(browser.span, None);
+        assert_eq!(info.evidence.os.unwrap().token, "Windows");
+
+        let parser = UserAgentParser::builder()
+            .rule(Rule::new(r"AcmeApp/[\d.]+").browser(Browser::Other("AcmeApp".to_string())))
+            .build()
+            .unwrap();
+        let info = parser.detect("AcmeApp/3.2 (Linux)");
+        let browser = info.evidence.browser.unwrap();
+        assert_eq!(browser.token, "AcmeApp/3.2");
+        assert_eq!(browser.span, Some(0..11));
+        assert_eq!(
+            browser.rule,
+            Source::CustomRule {
+                pattern: r"AcmeApp/[\d.]+".to_string()
+            }
+        );
+        assert_eq!(browser.confidence, Confidence::Medium);
+    }
+
+    // Explain Mode
+    #[test]
+    fn test_explain() {
+        let ua = "Mozilla/5.0 (Linux; U; Android 4.0.3; en-us; KFTT Build/IML74K) AppleWebKit/537.36 (KHTML, like Gecko) Silk/3.68 like Chrome/39.0.2171.93 Safari/537.36";
+        let explanation = UserAgentParser::explain(ua);
+        let stages: Vec<_> = explanation
+            .steps
+            .iter()
+            .map(|step| (step.field, step.stage.as_str()))
+            .collect();
+        assert_eq!(
+            stages,
+            [
+                (Field::Os, "OS_REGEX[0]"),
+                (Field::Browser, "BROWSER_REGEX[0]"),
+                (Field::DeviceType, "DEVICE_REGEX[0]"),
+                (Field::DeviceType, "DEVICE_REGEX[1]"),
+                (Field::DeviceType, "KNOWN_BOTS"),
+                (Field::DeviceType, "desktop fallback"),
+            ]
+        );
+        let browser = &explanation.steps[1];
+        assert_eq!(browser.matched.as_ref().unwrap().token, "Silk");
+        assert_eq!(browser.outcome, Outcome::Detected("Silk".to_string()));
+        // The Kindle model code is not a known tablet, so the generic Android keyword decides
+        assert_eq!(explanation.steps[2].outcome, Outcome::NoMatch);
+        assert_eq!(explanation.steps[3].matched.as_ref().unwrap().token, "Android");
+        assert_eq!(explanation.steps[3].outcome, Outcome::Detected("Mobile".to_string()));
+        assert_eq!(explanation.steps[4].outcome, Outcome::NoMatch);
+        assert_eq!(explanation.steps[5].outcome, Outcome::Skipped);
+        assert_eq!(explanation.info.browser, UserAgentParser::parse(ua).browser);
+
+        // Tiers that match nothing, or match a keyword without a value, keep going
+        let explanation = UserAgentParser::explain(
+            "Mozilla/5.0 (Series40; Nokia311/03.81; Profile/MIDP-2.1 Configuration/CLDC-1.1) Gecko/20100401 S40OviBrowser/3.8.1.0.5 NokiaBrowser/8.5.0",
+        );
+        let browser: Vec<_> = explanation
+            .steps
+            .iter()
+            .filter(|step| step.field == Field::Browser)
+            .map(|step| &step.outcome)
+            .collect();
+        assert_eq!(browser, [&Outcome::Unmapped, &Outcome::NoMatch]);
+        let text = explanation.to_string();
+        assert!(text.contains(
+            "BROWSER_REGEX[0] (Browser): matched \"NokiaBrowser\" at 119..131, no known value, \
+             trying the next tier"
+        ));
+        assert!(text.contains("BROWSER_REGEX[1] (Browser): no match, no tiers left"), "{}", text);
+
+        // The last tier says so when nothing is left to try
+        let explanation = UserAgentParser::explain("SomeTool/1.0");
+        let text = explanation.to_string();
+        assert!(text.contains("OS_REGEX[1] (Os): no match, no tiers left"), "{}", text);
+        assert!(text.contains("desktop fallback (DeviceType): no match, no tiers left"), "{}", text);
+        assert!(explanation.steps.last().unwrap().last);
+
+        let explanation = UserAgentParser::explain("SomeTool/1.0 (Macintosh)");
+        let fallback = explanation.steps.last().unwrap();
+        assert_eq!(fallback.stage, "desktop fallback");
+        assert_eq!(fallback.matched.as_ref().unwrap().token, "Macintosh");
+        assert_eq!(fallback.outcome, Outcome::Detected("Desktop".to_string()));
+        assert!(explanation.to_string().contains(
+            "desktop fallback (DeviceType): matched \"Macintosh\" at 14..23, detected Desktop"
+        ));
+
+        let explanation = UserAgentParser::explain("MyApp/2.4.1 (Watch6,18; watchOS 10.0)");
+        let apple = explanation.steps.last().unwrap();
+        assert_eq!(apple.stage, "Apple model");
+        assert_eq!(apple.outcome, Outcome::Detected("Smartwatch".to_string()));
+    }
+
+    // Bot Details
+    #[test]
+    fn test_bot_details() {
+        fn bot(ua: &str) -> agent_parser_ro::Bot {
+            let info = UserAgentParser::parse(ua);
+            assert_eq!(info.device_type, DeviceType::Bot, "{}", ua);
+            info.bot.unwrap_or_else(|| panic!("no bot for {}", ua))
+        }
+
+        let googlebot = bot("Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)");
+        assert_eq!(googlebot.name, "Googlebot Desktop");
+        assert_eq!(googlebot.operator.as_deref(), Some("Google"));
+        assert_eq!(googlebot.category, BotCategory::SearchEngine);
+        assert_eq!(googlebot.version.unwrap().raw, "2.1");
+        assert_eq!(googlebot.url.as_deref(), Some("http://www.google.com/bot.html"));
+        assert_eq!(googlebot.robots_token.as_deref(), Some("Googlebot"));
+
+        let smartphone = bot(
+            "Mozilla/5.0 (Linux; Android 6.0.1; Nexus 5X Build/MMB29P) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.216 Mobile Safari/537.36 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+        );
+        assert_eq!(smartphone.name, "Googlebot Smartphone");
+
+        // YandexBot has no browser value but is still a fully described bot
+        let info = UserAgentParser::parse("Mozilla/5.0 (compatible; YandexBot/3.0; +http://yandex.com/bots)");
+        assert_eq!(info.browser, Browser::Unknown);
+        let yandex = info.bot.unwrap();
+        assert_eq!((yandex.name.as_str(), yandex.operator.as_deref()), ("YandexBot", Some("Yandex")));
+        assert_eq!(yandex.url.as_deref(), Some("http://yandex.com/bots"));
+
+        let cases = [
+            ("facebookexternalhit/1.1 (+http://www.facebook.com/externalhit_uatext.php)", "Facebook External Hit", BotCategory::SocialPreview),
+            ("Slackbot-LinkExpanding 1.0 (+https://api.slack.com/robots)", "Slackbot Link Expanding", BotCategory::SocialPreview),
+            ("Mozilla/5.0 (compatible; AhrefsBot/7.0; +http://ahrefs.com/robot/)", "AhrefsBot", BotCategory::SeoTool),
+            ("Screaming Frog SEO Spider/19.4", "Screaming Frog SEO Spider", BotCategory::SeoTool),
+            ("Mozilla/5.0+(compatible; UptimeRobot/2.0; http://www.uptimerobot.com/)", "UptimeRobot", BotCategory::Monitoring),
+            ("Pingdom.com_bot_version_1.4_(http://www.pingdom.com/)", "Pingdom", BotCategory::Monitoring),
+            ("Feedly/1.0 (+http://www.feedly.com/fetcher.html; 42 subscribers; like FeedFetcher-Google)", "Feedly", BotCategory::FeedFetcher),
+            ("Mozilla/5.0 (compatible; archive.org_bot +http://archive.org/details/archive.org_bot)", "archive.org_bot", BotCategory::Archiver),
+            ("Mozilla/5.0 AppleWebKit/537.36 (KHTML, like Gecko; compatible; GPTBot/1.2; +https://openai.com/gptbot)", "GPTBot", BotCategory::AiCrawler),
+            ("Mozilla/5.0 (compatible; Yahoo! Slurp; http://help.yahoo.com/help/us/ysearch/slurp)", "Yahoo! Slurp", BotCategory::SearchEngine),
+        ];
+        for (ua, name, category) in cases {
+            let found = bot(ua);
+            assert_eq!((found.name.as_str(), found.category), (name, category), "{}", ua);
+        }
+
+        // Unknown clients calling themselves bots are generic scrapers
+        let unknown = bot("Mozilla/5.0 (compatible; AcmeCrawler/0.9; +https://acme.example/crawler)");
+        assert_eq!(unknown.name, "AcmeCrawler");
+        assert_eq!(unknown.operator, None);
+        assert_eq!(unknown.category, BotCategory::Scraper);
+        assert_eq!(unknown.url.as_deref(), Some("https://acme.example/crawler"));
+
+        // Brands ending in "bot" are not bots without a name/version token
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Linux; Android 9; CUBOT X19) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+        );
+        assert!(info.bot.is_none());
+        assert_eq!(info.device_type, DeviceType::Mobile);
+    }
+
+    // AI Crawlers and Assistant Fetchers
+    #[test]
+    fn test_ai_bots() {
+        let cases = [
+            ("Mozilla/5.0 AppleWebKit/537.36 (KHTML, like Gecko; compatible; GPTBot/1.2; +https://openai.com/gptbot)", "GPTBot", "OpenAI", BotCategory::AiCrawler),
+            ("Mozilla/5.0 AppleWebKit/537.36 (KHTML, like Gecko; compatible; ClaudeBot/1.0; +claudebot@anthropic.com)", "ClaudeBot", "Anthropic", BotCategory::AiCrawler),
+            ("CCBot/2.0 (https://commoncrawl.org/faq/)", "CCBot", "Common Crawl", BotCategory::AiCrawler),
+            ("Mozilla/5.0 AppleWebKit/537.36 (KHTML, like Gecko; compatible; PerplexityBot/1.0; +https://perplexity.ai/perplexitybot)", "PerplexityBot", "Perplexity", BotCategory::AiCrawler),
+            ("Mozilla/5.0 (Linux; Android 5.0) AppleWebKit/537.36 (KHTML, like Gecko) Mobile Safari/537.36 (compatible; Bytespider; spider-feedback@bytedance.com)", "Bytespider", "ByteDance", BotCategory::AiCrawler),
+            ("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_10_1) AppleWebKit/600.2.5 (KHTML, like Gecko) Version/8.0.2 Safari/600.2.5 (Amazonbot/0.1; +https://developer.amazon.com/support/amazonbot)", "Amazonbot", "Amazon", BotCategory::AiCrawler),
+            ("meta-externalagent/1.1 (+https://developers.facebook.com/docs/sharing/webmasters/crawler)", "meta-externalagent", "Meta", BotCategory::AiCrawler),
+            ("Mozilla/5.0 AppleWebKit/537.36 (KHTML, like Gecko); compatible; ChatGPT-User/1.0; +https://openai.com/bot", "ChatGPT-User", "OpenAI", BotCategory::AiAssistant),
+            ("Mozilla/5.0 AppleWebKit/537.36 (KHTML, like Gecko; compatible; Perplexity-User/1.0; +https://perplexity.ai/perplexity-user)", "Perplexity-User", "Perplexity", BotCategory::AiAssistant),
+        ];
+        for (ua, robots_token, operator, category) in cases {
+            let info = UserAgentParser::parse(ua);
+            assert_eq!(info.device_type, DeviceType::Bot, "{}", ua);
+            let bot = info.bot.unwrap_or_else(|| panic!("no bot for {}", ua));
+            assert_eq!(bot.robots_token.as_deref(), Some(robots_token), "{}", ua);
+            assert_eq!(bot.operator.as_deref(), Some(operator), "{}", ua);
+            assert_eq!(bot.category, category, "{}", ua);
+        }
+
+        // Fetchers that append their info URL outside a comment still report it
+        let info = UserAgentParser::parse(cases[7].0);
+        assert_eq!(info.bot.unwrap().url.as_deref(), Some("https://openai.com/bot"));
+
+        let info = UserAgentParser::parse("Mozilla/5.0 (compatible; Yahoo! Slurp; http://help.yahoo.com/help/us/ysearch/slurp)");
+        assert_eq!(info.bot.unwrap().robots_token.as_deref(), Some("Slurp"));
+    }
+
+    // Bot Verification
+    struct StubResolver {
+        reverse: HashMap<IpAddr, Vec<&'static str>>,
+        forward: HashMap<&'static str, Vec<IpAddr>>,
+    }
+
+    impl Resolver for StubResolver {
+        fn reverse(&self, ip: IpAddr) -> io::Result<Vec<String>> {
+            match self.reverse.get(&ip) {
+                Some(hosts) => Ok(hosts.iter().map(|host| host.to_string()).collect()),
+                None => Err(io::ErrorKind::NotFound.into()),
+            }
+        }
+
+        fn forward(&self, host: &str) -> io::Result<Vec<IpAddr>> {
+            Ok(self.forward.get(host).cloned().unwrap_or_default())
+        }
+    }
+
+    #[test]
+    fn test_bot_verification() {
+        let ip = |ip: &str| ip.parse::<IpAddr>().unwrap();
+        let resolver = StubResolver {
+            reverse: HashMap::from([
+                (ip("5.255.253.10"), vec!["5-255-253-10.spider.yandex.com."]),
+                (ip("203.0.113.7"), vec!["crawl.yandex.com.attacker.example"]),
+                (ip("203.0.113.8"), vec!["fake.spider.yandex.com"]),
+                (ip("34.66.0.5"), vec!["5.0.66.34.bc.googleusercontent.com"]),
+                (ip("17.241.75.1"), vec!["17-241-75-1.applebot.apple.com"]),
+            ]),
+            forward: HashMap::from([
+                ("5-255-253-10.spider.yandex.com", vec![ip("5.255.253.10")]),
+                ("crawl.yandex.com.attacker.example", vec![ip("203.0.113.7")]),
+                ("fake.spider.yandex.com", vec![ip("5.255.253.11")]),
+                ("5.0.66.34.bc.googleusercontent.com", vec![ip("34.66.0.5")]),
+                ("17-241-75-1.applebot.apple.com", vec![ip("17.241.75.1")]),
+            ]),
+        };
+
+        let googlebot = UserAgentParser::parse(
+            "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+        );
+        assert_eq!(verify_bot(&googlebot, ip("66.249.66.1"), &resolver), Verification::Verified);
+        assert_eq!(
+            verify_bot(&googlebot, ip("2001:4860:4801:10::1"), &resolver),
+            Verification::Verified
+        );
+        // IPv4 addresses mapped into IPv6 are checked as IPv4
+        assert_eq!(
+            verify_bot(&googlebot, ip("::ffff:66.249.66.1"), &resolver),
+            Verification::Verified
+        );
+        // Outside the published ranges, with no reverse DNS to fall back on
+        assert_eq!(verify_bot(&googlebot, ip("198.51.100.1"), &resolver), Verification::Unverifiable);
+        // Any Compute Engine VM can have a host under googleusercontent.com
+        assert_eq!(verify_bot(&googlebot, ip("34.66.0.5"), &resolver), Verification::Spoofed);
+
+        let bingbot = UserAgentParser::parse(
+            "Mozilla/5.0 (compatible; bingbot/2.0; +http://www.bing.com/bingbot.htm)",
+        );
+        assert_eq!(verify_bot(&bingbot, ip("157.55.39.1"), &resolver), Verification::Verified);
+        assert_eq!(verify_bot(&bingbot, ip("203.0.113.7"), &resolver), Verification::Spoofed);
+
+        // Forward-confirmed reverse DNS
+        let yandex = UserAgentParser::parse(
+            "Mozilla/5.0 (compatible; YandexBot/3.0; +http://yandex.com/bots)",
+        );
+        assert_eq!(verify_bot(&yandex, ip("5.255.253.10"), &resolver), Verification::Verified);
+        assert_eq!(verify_bot(&yandex, ip("203.0.113.7"), &resolver), Verification::Spoofed);
+        assert_eq!(verify_bot(&yandex, ip("203.0.113.8"), &resolver), Verification::Spoofed);
+        assert_eq!(verify_bot(&yandex, ip("198.51.100.1"), &resolver), Verification::Unverifiable);
+
+        let applebot = UserAgentParser::parse(
+            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.4 Safari/605.1.15 (Applebot/0.1; +http://www.apple.com/go/applebot)",
+        );
+        assert_eq!(verify_bot(&applebot, ip("17.241.75.1"), &resolver), Verification::Verified);
+        // Apple publishes Applebot's ranges
+        assert_eq!(verify_bot(&applebot, ip("17.246.15.9"), &resolver), Verification::Verified);
+        // Apple's own network is not proof of Applebot
+        assert_eq!(verify_bot(&applebot, ip("17.5.7.8"), &resolver), Verification::Unverifiable);
+
+        // Operator tools that anyone can run are not crawlers
+        let lighthouse = UserAgentParser::parse(
+            "Mozilla/5.0 (Linux; Android 11; moto g power (2022)) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/119.0.0.0 Mobile Safari/537.36 Chrome-Lighthouse",
+        );
+        assert_eq!(lighthouse.bot.as_ref().unwrap().operator.as_deref(), Some("Google"));
+        assert_eq!(verify_bot(&lighthouse, ip("198.51.100.1"), &resolver), Verification::Unverifiable);
+
+        // Browsers cannot be checked
+        let chrome = UserAgentParser::parse(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        );
+        assert_eq!(verify_bot(&chrome, ip("66.249.66.1"), &resolver), Verification::Unverifiable);
+
+        // Updated ranges replace the embedded ones for all of the operator's crawlers
+        let verifier = BotVerifier::default()
+            .ip_ranges("Google", ["192.0.2.0/24"])
+            .unwrap()
+            .domains("Googlebot", Vec::<&str>::new());
+        assert_eq!(verifier.verify(&googlebot, ip("192.0.2.9"), &resolver), Verification::Verified);
+        assert_eq!(verifier.verify(&googlebot, ip("66.249.66.1"), &resolver), Verification::Spoofed);
+        let adsbot = UserAgentParser::parse("AdsBot-Google (+http://www.google.com/adsbot.html)");
+        assert_eq!(verifier.verify(&adsbot, ip("192.0.2.9"), &resolver), Verification::Verified);
+
+        let result = BotVerifier::default().ip_ranges("Google", ["66.249.64.0/33"]);
+        assert!(matches!(result, Err(Error::InvalidIpRange { range }) if range == "66.249.64.0/33"));
+    }
+
+    // robots.txt Group Matching
+    #[test]
+    fn test_robots_txt() {
+        let robots = RobotsTxt::parse(
+            "# Example\n\
+             User-agent: googlebot\n\
+             User-agent: Baiduspider/2.0 # versions are ignored\n\
+             Disallow: /search\n\
+             Allow: /search/about\n\
+             Sitemap: https://example.com/sitemap.xml\n\
+             \n\
+             User-agent: YandexBot\n\
+             Disallow: /*.pdf$\n\
+             Disallow: /tmp/*/cache\n\
+             \n\
+             User-agent: GOOGLEBOT\n\
+             Disallow: /drafts\n\
+             \n\
+             User-agent: *\n\
+             Disallow: /\n\
+             Allow: /public\n\
+             Disallow: /publ\n\
+             Disallow: /caf\u{e9}\n",
+        );
+        assert_eq!(robots.groups.len(), 4);
+        assert_eq!(robots.groups[0].user_agents, ["googlebot", "Baiduspider"]);
+
+        // Groups naming the same crawler are merged, whatever the case
+        let googlebot = UserAgentParser::parse(
+            "Mozilla/5.0 (Linux; Android 6.0.1; Nexus 5X Build/MMB29P) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.129 Mobile Safari/537.36 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+        );
+        let group = robots.group_for(&googlebot).unwrap();
+        assert_eq!(group.user_agents, ["googlebot", "Baiduspider", "GOOGLEBOT"]);
+        assert!(!robots.is_allowed(&googlebot, "/search?q=rust"));
+        assert!(!robots.is_allowed(&googlebot, "/drafts/post"));
+        assert!(robots.is_allowed(&googlebot, "/search/about"));
+        assert!(robots.is_allowed(&googlebot, "/"));
+        assert_eq!(group.matching_rule("/search/about/team").unwrap().pattern, "/search/about");
+
+        let baiduspider = UserAgentParser::parse(
+            "Mozilla/5.0 (compatible; Baiduspider/2.0; +http://www.baidu.com/search/spider.html)",
+        );
+        assert!(!robots.is_allowed(&baiduspider, "/search"));
+        assert!(robots.is_allowed(&baiduspider, "/drafts"));
+
+        // Wildcards and end anchors
+        let yandex = UserAgentParser::parse(
+            "Mozilla/5.0 (compatible; YandexBot/3.0; +http://yandex.com/bots)",
+        );
+        assert!(!robots.is_allowed(&yandex, "/files/report.pdf"));
+        assert!(robots.is_allowed(&yandex, "/files/report.pdf?download=1"));
+        assert!(!robots.is_allowed(&yandex, "/tmp/a/b/cache/1"));
+        assert!(robots.is_allowed(&yandex, "/tmp/cache"));
+
+        // Other crawlers and browsers fall under `*`, where the longest rule wins
+        // and allow wins a tie
+        let bingbot = UserAgentParser::parse(
+            "Mozilla/5.0 (compatible; bingbot/2.0; +http://www.bing.com/bingbot.htm)",
+        );
+        assert_eq!(robots.group_for(&bingbot).unwrap().user_agents, ["*"]);
+        let browser = UserAgentParser::parse(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0",
+        );
+        assert!(!robots.is_allowed(&browser, "/index.html"));
+        assert!(robots.is_allowed(&browser, "/public/index.html"));
+        assert!(!robots.is_allowed(&browser, "/publ"));
+        assert!(robots.is_allowed(&browser, "/robots.txt"));
+        // Paths and patterns are compared percent-encoded
+        let star = robots.group_for_token("*").unwrap();
+        assert_eq!(star.matching_rule("/caf%c3%a9/menu").unwrap().pattern, "/caf\u{e9}");
+
+        // Without a matching group everything is allowed
+        let robots = RobotsTxt::parse("User-agent: Googlebot\nDisallow: /\n");
+        assert!(robots.group_for(&bingbot).is_none());
+        assert!(robots.is_allowed(&bingbot, "/anything"));
+        assert!(!robots.is_allowed(&googlebot, "/anything"));
+
+        // A byte order mark does not hide the first user-agent line
+        let robots = RobotsTxt::parse("\u{feff}User-agent: Googlebot\nDisallow: /\n");
+        assert!(!robots.is_allowed(&googlebot, "/anything"));
+    }
+
+    // HTTP Client Libraries and CLI Tools
+    #[test]
+    fn test_libraries() {
+        let cases = [
+            ("curl/8.4.0", "curl", "8.4.0"),
+            ("Wget/1.21.4 (linux-gnu)", "Wget", "1.21.4"),
+            ("python-requests/2.31.0", "python-requests", "2.31.0"),
+            ("Python/3.11 aiohttp/3.9.1", "aiohttp", "3.9.1"),
+            ("Go-http-client/2.0", "Go-http-client", "2.0"),
+            ("okhttp/4.12.0", "OkHttp", "4.12.0"),
+            ("Apache-HttpClient/4.5.14 (Java/17.0.8)", "Apache HttpClient", "4.5.14"),
+            ("Java/1.8.0_292", "Java", "1.8.0_292"),
+            ("node-fetch/1.0 (+https://github.com/bitinn/node-fetch)", "node-fetch", "1.0"),
+            ("axios/1.6.2", "axios", "1.6.2"),
+            ("reqwest/0.11.22", "reqwest", "0.11.22"),
+            ("PostmanRuntime/7.36.0", "Postman", "7.36.0"),
+            ("HTTPie/3.2.2", "HTTPie", "3.2.2"),
+        ];
+        for (ua, name, version) in cases {
+            let info = UserAgentParser::parse(ua);
+            assert_eq!(info.client_kind, ClientKind::Library, "{}", ua);
+            assert_eq!(info.browser, Browser::Unknown, "{}", ua);
+            let library = info.library.unwrap_or_else(|| panic!("no library for {}", ua));
+            assert_eq!(library.name, name, "{}", ua);
+            assert_eq!(library.version.unwrap().raw, version, "{}", ua);
+        }
+
+        let info = UserAgentParser::parse("curl/7.29.0");
+        let version = info.library.unwrap().version.unwrap();
+        assert_eq!((version.major, version.minor, version.patch), (7, Some(29), Some(0)));
+
+        // Browsers and bots keep their own kind
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        );
+        assert_eq!((info.client_kind, info.library), (ClientKind::Browser, None));
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+        );
+        assert_eq!(info.client_kind, ClientKind::Bot);
+        assert_eq!(UserAgentParser::parse("").client_kind, ClientKind::Unknown);
+    }
+
+    // Headless Browsers and Automation
+    #[test]
+    fn test_automation() {
+        let cases = [
+            ("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) HeadlessChrome/120.0.6099.28 Safari/537.36", vec![Automation::HeadlessChrome]),
+            ("Mozilla/5.0 (Unknown; Linux x86_64) AppleWebKit/538.1 (KHTML, like Gecko) PhantomJS/2.1.1 Safari/538.1", vec![Automation::PhantomJS]),
+            ("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Selenium/4.15.0", vec![Automation::Selenium]),
+            ("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Cypress/13.6.0 Chrome/118.0.5993.159 Electron/27.1.3 Safari/537.36", vec![Automation::Cypress]),
+            ("Mozilla/5.0 (linux) AppleWebKit/537.36 (KHTML, like Gecko) jsdom/22.1.0", vec![Automation::Jsdom]),
+            ("Mozilla/5.0 (Linux; Android 11; moto g power (2022)) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/119.0.0.0 Mobile Safari/537.36 Chrome-Lighthouse", vec![Automation::Lighthouse]),
+            ("Mozilla/5.0 (Windows NT 6.1; WOW64) AppleWebKit/537.36 (KHTML, like Gecko; Google Page Speed Insights) Chrome/27.0.1453 Safari/537.36", vec![Automation::PageSpeedInsights]),
+            ("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 GTmetrix", vec![Automation::GTmetrix]),
+            ("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 PTST/231109.150107", vec![Automation::WebPageTest]),
+            ("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) HeadlessChrome/119.0.0.0 Safari/537.36 Puppeteer Chrome-Lighthouse", vec![Automation::HeadlessChrome, Automation::Puppeteer, Automation::Lighthouse]),
+            // Tool names inside other words are not signals
+            ("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 LighthouseHQ/2.0", vec![]),
+            ("Mozilla/5.0 (Linux; Android 13; SeleniumX) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36 MyPlaywrightsApp/1.0", vec![]),
+        ];
+        for (ua, automation) in &cases {
+            assert_eq!(&UserAgentParser::parse(ua).automation, automation, "{}", ua);
+        }
+
+        // Headless Chrome still parses as Chrome
+        let info = UserAgentParser::parse(cases[0].0);
+        assert_eq!(info.browser, Browser::Chrome);
+        assert_eq!(info.browser_version.unwrap().major, 120);
+
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        );
+        assert!(info.automation.is_empty());
+
+        // Testing services are monitoring bots as well as automation signals
+        for ua in [cases[5].0, cases[6].0, cases[7].0] {
+            let info = UserAgentParser::parse(ua);
+            assert_eq!(info.bot.unwrap().category, BotCategory::Monitoring, "{}", ua);
+            assert_eq!(info.automation.len(), 1, "{}", ua);
+        }
+    }
+
+    // Client Stack
+    #[test]
+    fn test_client_stack() {
+        let cases = [
+            (
+                "Mozilla/5.0 (Linux; Android 14; Pixel 8 Build/UQ1A.240105.004; wv) AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 Chrome/120.0.6099.210 Mobile Safari/537.36 [FB_IAB/FB4A;FBAV/446.0.0.40.117;]",
+                Some((App::Facebook, Some("446.0.0.40.117"))), Some(Embedding::WebView), Browser::Chrome, Some("120.0.6099.210"),
+            ),
+            (
+                "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Mobile/15E148 Instagram 309.1.1.28.108 (iPhone14,5; iOS 17_1_2; en_US; en; scale=3.00; 1170x2532; 541635890)",
+                Some((App::Instagram, Some("309.1.1.28.108"))), Some(Embedding::WebView), Browser::Safari, None,
+            ),
+            (
+                "Mozilla/5.0 (Linux; Android 13; SM-G991B Build/TP1A.220624.014; wv) AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 Chrome/119.0.6045.163 Mobile Safari/537.36 trill_2023205030 JsSdk/1.0 NetType/WIFI Channel/googleplay AppName/musical_ly app_version/32.5.3 ByteLocale/en",
+                Some((App::TikTok, Some("32.5.3"))), Some(Embedding::WebView), Browser::Chrome, Some("119.0.6045.163"),
+            ),
+            (
+                "Mozilla/5.0 (iPhone; CPU iPhone OS 17_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Mobile/15E148 Safari Line/13.20.0",
+                Some((App::Line, Some("13.20.0"))), Some(Embedding::WebView), Browser::Safari, None,
+            ),
+            (
+                "Mozilla/5.0 (iPhone; CPU iPhone OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Mobile/15E148 MicroMessenger/8.0.42(0x18002a2d) NetType/WIFI Language/zh_CN",
+                Some((App::WeChat, Some("8.0.42"))), Some(Embedding::WebView), Browser::Safari, None,
+            ),
+            (
+                "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) GSA/300.0.598994205 Mobile/15E148 Safari/604.1",
+                Some((App::GoogleApp, Some("300.0.598994205"))), Some(Embedding::WebView), Browser::Safari, None,
+            ),
+            // An app token on a full browser user agent does not tell the embedding
+            (
+                "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1 [Pinterest/iOS]",
+                Some((App::Pinterest, None)), None, Browser::Safari, Some("17.1"),
+            ),
+            (
+                "Mozilla/5.0 (Linux; Android 14; Pixel 7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36 [LinkedInApp]/9.29.2204",
+                Some((App::LinkedIn, Some("9.29.2204"))), None, Browser::Chrome, Some("120.0.0.0"),
+            ),
+            // The app's own API requests
+            (
+                "Instagram 250.0.0.21.109 Android (31/12; 420dpi; 1080x2400; samsung; SM-G991B; o1s; exynos2100; en_US; 394071253)",
+                Some((App::Instagram, Some("250.0.0.21.109"))), None, Browser::Instagram, Some("250.0.0.21.109"),
+            ),
+            // Web views without a known host app
+            (
+                "Mozilla/5.0 (Linux; Android 12; Pixel 6; wv) AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 Chrome/120.0.0.0 Mobile Safari/537.36",
+                None, Some(Embedding::WebView), Browser::Chrome, Some("120.0.0.0"),
+            ),
+            (
+                "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Mobile/15E148",
+                None, Some(Embedding::WebView), Browser::Safari, None,
+            ),
+            // Ordinary browsers have a single layer
+            (
+                "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) CriOS/120.0.6099.119 Mobile/15E148 Safari/604.1",
+                None, None, Browser::Chrome, Some("120.0.6099.119"),
+            ),
+            (
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0",
+                None, None, Browser::Firefox, Some("121.0"),
+            ),
+            // App names in other products or in bots are not host apps
+            (
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 SnapchatCamera/1.0",
+                None, None, Browser::Chrome, Some("120.0.0.0"),
+            ),
+        ];
+        for (ua, host, embedding, browser, browser_version) in &cases {
+            let stack = UserAgentParser::parse(ua).client_stack;
+            let app = stack.host.map(|host| (host.app, host.version.map(|version| version.raw)));
+            let expected = host.map(|(app, version)| (app, version.map(String::from)));
+            assert_eq!(app, expected, "{}", ua);
+            assert_eq!(stack.embedding, *embedding, "{}", ua);
+            assert_eq!(&stack.browser, browser, "{}", ua);
+            let version = stack.browser_version.map(|version| version.raw);
+            assert_eq!(version.as_deref(), *browser_version, "{}", ua);
+        }
+
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (compatible; Pinterestbot/1.0; +http://www.pinterest.com/bot.html)",
+        );
+        assert!(info.bot.is_some());
+        assert_eq!(info.client_stack.host, None);
+        assert_eq!(info.client_stack.embedding, None);
+
+        // The flat browser field names the host app
+        let info = UserAgentParser::parse(cases[0].0);
+        assert_eq!(info.browser, Browser::Facebook);
+        assert_eq!(info.client_stack.engine, Engine::Blink);
+        assert_eq!(info.client_stack.engine_version.unwrap().major, 120);
+    }
+
+    // Facebook Metadata
+    #[test]
+    fn test_facebook_metadata() {
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (iPhone; CPU iPhone OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Mobile/15E148 [FBAN/FBIOS;FBDV/iPhone14,3;FBMD/iPhone;FBSN/iOS;FBSV/16.6;FBSS/3;FBID/phone;FBLC/en_US;FBOP/5]",
+        );
+        let facebook = info.facebook.unwrap();
+        assert_eq!(facebook.app_name.as_deref(), Some("FBIOS"));
+        assert_eq!(facebook.app_version, None);
+        assert_eq!(facebook.device.as_deref(), Some("iPhone14,3"));
+        assert_eq!(facebook.os_version.unwrap().raw, "16.6");
+        assert_eq!(facebook.locale.as_deref(), Some("en_US"));
+        assert_eq!(facebook.carrier, None);
+        assert_eq!(facebook.form_factor, Some(DeviceType::Mobile));
+        assert_eq!(facebook.screen_scale, Some(3.0));
+        assert_eq!(info.browser, Browser::Facebook);
+        let evidence = info.evidence.browser.unwrap();
+        assert_eq!((evidence.token.as_str(), evidence.rule), ("FBAN/FBIOS", Source::FacebookMetadata));
+
+        // Android builds fill in what the reduced user agent leaves out
+        let ua = "Mozilla/5.0 (Linux; Android 10; K; wv) AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 Chrome/120.0.0.0 Mobile Safari/537.36 [FB_IAB/FB4A;FBAV/446.0.0.40.117;FBDM/{density=2.625,width=1080,height=2220};FBLC/de_DE;FBCR/T-Mobile;FBMF/samsung;FBBD/samsung;FBPN/com.facebook.katana;FBDV/SM-G991B;FBSV/13;FBOP/1;FBCA/arm64-v8a:;]";
+        let info = UserAgentParser::parse(ua);
+        let facebook = info.facebook.clone().unwrap();
+        assert_eq!(facebook.app_name.as_deref(), Some("FB4A"));
+        assert_eq!(facebook.app_version.unwrap().raw, "446.0.0.40.117");
+        assert_eq!(facebook.carrier.as_deref(), Some("T-Mobile"));
+        assert_eq!(facebook.form_factor, None);
+        assert_eq!(facebook.screen_scale, Some(2.625));
+        assert_eq!(info.browser, Browser::Facebook);
+        assert_eq!(info.browser_version.unwrap().major, 446);
+        assert_eq!(info.os, OperatingSystem::Android);
+        assert_eq!(info.os_version.unwrap().major, 10);
+        assert_eq!(info.device.model_code.as_deref(), Some("SM-G991B"));
+        assert_eq!(info.device.brand.as_deref(), Some("Samsung"));
+        assert_eq!(info.device_type, DeviceType::Mobile);
+
+        let explanation = UserAgentParser::explain(ua);
+        let step = explanation.steps.last().unwrap();
+        assert_eq!((step.field, step.stage.as_str()), (Field::Browser, "Facebook metadata"));
+        assert_eq!(step.matched.as_ref().unwrap().token, "FB_IAB/FB4A");
+
+        // The form factor decides the device type
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (iPad; CPU OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Mobile/15E148 [FBAN/FBIOS;FBAV/426.0.0.45.108;FBBV/523456789;FBDV/iPad13,18;FBMD/iPad;FBSN/iPadOS;FBSV/16.6;FBSS/2;FBID/tablet;FBLC/fr_FR;FBOP/5]",
+        );
+        assert_eq!(info.device_type, DeviceType::Tablet);
+        assert_eq!(info.evidence.device_type.unwrap().token, "FBID/tablet");
+        assert_eq!(info.device.model.as_deref(), Some("iPad (10th generation)"));
+
+        assert!(UserAgentParser::parse("Mozilla/5.0 (X11; Linux x86_64)").facebook.is_none());
+    }
+
+    // Instagram App Metadata
+    #[test]
+    fn test_instagram_metadata() {
+        let info = UserAgentParser::parse(
+            "Instagram 250.0.0.21.109 Android (31/12; 420dpi; 1080x2400; samsung; SM-G991B; o1s; exynos2100; en_US; 394071253)",
+        );
+        assert_eq!(info.browser, Browser::Instagram);
+        assert_eq!(info.os, OperatingSystem::Android);
+        assert_eq!(info.os_version.as_ref().unwrap().raw, "12");
+        assert_eq!(info.device.model_code.as_deref(), Some("SM-G991B"));
+        assert_eq!(info.device.brand.as_deref(), Some("Samsung"));
+        assert_eq!(info.device_type, DeviceType::Mobile);
+        let instagram = info.instagram.unwrap();
+        assert_eq!(instagram.app_version.unwrap().raw, "250.0.0.21.109");
+        assert_eq!(instagram.api_level, Some(31));
+        assert_eq!(instagram.os_version.unwrap().raw, "12");
+        assert_eq!(instagram.dpi, Some(420));
+        assert_eq!(instagram.screen_scale, Some(2.625));
+        assert_eq!((instagram.width, instagram.height), (Some(1080), Some(2400)));
+        assert_eq!(instagram.manufacturer.as_deref(), Some("samsung"));
+        assert_eq!(instagram.model.as_deref(), Some("SM-G991B"));
+        assert_eq!(instagram.codename.as_deref(), Some("o1s"));
+        assert_eq!(instagram.chipset.as_deref(), Some("exynos2100"));
+        assert_eq!(instagram.locale.as_deref(), Some("en_US"));
+        assert_eq!(instagram.build, Some(394071253));
+
+        // Unknown manufacturers are kept as reported
+        let info = UserAgentParser::parse(
+            "Instagram 301.0.0.33.110 Android (33/13; 480dpi; 1080x2412; Nothing; A063; Spacewar; qcom; de_DE; 517986727)",
+        );
+        assert_eq!(info.device.brand.as_deref(), Some("Nothing"));
+        assert_eq!(info.instagram.unwrap().chipset.as_deref(), Some("qcom"));
+
+        // The app's model code replaces the placeholder of a reduced user agent
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (Linux; Android 10; K; wv) AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 Chrome/120.0.0.0 Mobile Safari/537.36 Instagram 312.0.0.32.112 Android (34/14; 420dpi; 1080x2340; samsung; SM-S911B; dm1q; qcom; en_US; 548323757)",
+        );
+        assert_eq!(info.device.model_code.as_deref(), Some("SM-S911B"));
+        assert_eq!(info.device.brand.as_deref(), Some("Samsung"));
+        assert!(!info.frozen.device_model);
+
+        // The iOS app reports the hardware identifier and the OS in the same comment
+        let ua = "Instagram 309.1.1.28.108 (iPhone14,5; iOS 17_1_2; en_US; en; scale=3.00; 1170x2532; 541635890)";
+        let info = UserAgentParser::parse(ua);
+        assert_eq!(info.os, OperatingSystem::IOS);
+        assert_eq!(info.os_version.unwrap().raw, "17.1.2");
+        assert_eq!(info.device.model.as_deref(), Some("iPhone 13"));
+        assert_eq!(info.device_type, DeviceType::Mobile);
+        let instagram = info.instagram.unwrap();
+        assert_eq!(instagram.screen_scale, Some(3.0));
+        assert_eq!((instagram.width, instagram.height), (Some(1170), Some(2532)));
+        assert_eq!((instagram.api_level, instagram.chipset), (None, None));
+        assert_eq!(instagram.build, Some(541635890));
+
+        let explanation = UserAgentParser::explain(ua);
+        let step = explanation.steps.last().unwrap();
+        assert_eq!((step.field, step.stage.as_str()), (Field::Os, "Instagram metadata"));
+        assert_eq!(step.matched.as_ref().unwrap().token, "iOS 17_1_2");
+
+        // The in-app browser carries the same comment
+        let info = UserAgentParser::parse(
+            "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Mobile/15E148 Instagram 309.1.1.28.108 (iPhone14,5; iOS 17_1_2; en_US; en; scale=3.00; 1170x2532; 541635890)",
+        );
+        assert_eq!(info.browser, Browser::Instagram);
+        assert_eq!(info.instagram.unwrap().locale.as_deref(), Some("en_US"));
+    }